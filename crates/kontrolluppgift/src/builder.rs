@@ -0,0 +1,163 @@
+use crate::error::Error;
+use crate::{
+    Arendeinformation, Avsandare, Blankett, Blankettgemensamt, Kontrolluppgift, KontrolluppgiftType,
+};
+
+/// Builds a [Kontrolluppgift], numbering every Blankett in the order they are added
+#[derive(Debug)]
+pub struct KontrolluppgiftBuilder<'a> {
+    avsandare: Option<Avsandare<'a>>,
+    blankettgemensamt: Option<Blankettgemensamt<'a>>,
+    blanketter: Vec<(Arendeinformation<'a>, KontrolluppgiftType<'a>)>,
+    first_nummer: i64,
+}
+
+impl<'a> Default for KontrolluppgiftBuilder<'a> {
+    fn default() -> Self {
+        KontrolluppgiftBuilder {
+            avsandare: None,
+            blankettgemensamt: None,
+            blanketter: vec![],
+            first_nummer: 1,
+        }
+    }
+}
+
+impl<'a> KontrolluppgiftBuilder<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn avsandare(mut self, avsandare: Avsandare<'a>) -> Self {
+        self.avsandare = Some(avsandare);
+        self
+    }
+
+    pub fn blankettgemensamt(mut self, blankettgemensamt: Blankettgemensamt<'a>) -> Self {
+        self.blankettgemensamt = Some(blankettgemensamt);
+        self
+    }
+
+    /// The nummer of the first Blankett, 1 unless set
+    pub fn first_nummer(mut self, nummer: i64) -> Self {
+        self.first_nummer = nummer;
+        self
+    }
+
+    /// Adds a Blankett holding the form, such as a `KU10Type`
+    pub fn blankett(
+        mut self,
+        arendeinformation: Arendeinformation<'a>,
+        blankettinnehall: impl Into<KontrolluppgiftType<'a>>,
+    ) -> Self {
+        self.blanketter
+            .push((arendeinformation, blankettinnehall.into()));
+        self
+    }
+
+    pub fn build(self) -> Result<Kontrolluppgift<'a>, Error> {
+        let first_nummer = self.first_nummer;
+        Ok(Kontrolluppgift {
+            avsandare: self.avsandare.ok_or_else(|| Error::MissingElement {
+                missing: "Avsandare".into(),
+                reading: "Skatteverket".into(),
+            })?,
+            blankettgemensamt: self
+                .blankettgemensamt
+                .ok_or_else(|| Error::MissingElement {
+                    missing: "Blankettgemensamt".into(),
+                    reading: "Skatteverket".into(),
+                })?,
+            blanketter: self
+                .blanketter
+                .into_iter()
+                .zip(first_nummer..)
+                .map(|((arendeinformation, blankettinnehall), nummer)| Blankett {
+                    nummer,
+                    arendeinformation,
+                    blankettinnehall,
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ku10::{InkomsttagareKU10, KU10Type, UppgiftslamnareKU10};
+    use crate::{
+        from_str, to_string, IdentitetsbeteckningForPerson, Kontaktperson, TekniskKontaktperson,
+        Uppgiftslamnare,
+    };
+
+    fn ku10(specifikationsnummer: i32) -> KU10Type<'static> {
+        KU10Type::builder()
+            .kontant_bruttolon_mm(335000)
+            .inkomstar("2022")
+            .specifikationsnummer(specifikationsnummer)
+            .inkomsttagare(
+                InkomsttagareKU10::builder()
+                    .inkomsttagare(IdentitetsbeteckningForPerson::try_from("195111232079").unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .uppgiftslamnare(
+                UppgiftslamnareKU10::builder()
+                    .uppgiftslamnar_id("165599990602")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn kontrolluppgift_is_built() {
+        let arendeinformation = || {
+            Arendeinformation::builder()
+                .arendeagare("165599990602")
+                .period("2022")
+                .build()
+                .unwrap()
+        };
+        let ku = KontrolluppgiftBuilder::new()
+            .avsandare(
+                Avsandare::builder()
+                    .programnamn("Program")
+                    .organisationsnummer("165599990602")
+                    .teknisk_kontaktperson(TekniskKontaktperson::default())
+                    .skapad("2023-01-06T10:00:00")
+                    .build()
+                    .unwrap(),
+            )
+            .blankettgemensamt(Blankettgemensamt {
+                uppgiftslamnare: Uppgiftslamnare {
+                    uppgiftslamnare_pers_orgnr: "165599990602".into(),
+                    kontaktperson: Kontaktperson::default(),
+                },
+            })
+            .first_nummer(10)
+            .blankett(arendeinformation(), ku10(1))
+            .blankett(arendeinformation(), ku10(2))
+            .build()
+            .unwrap();
+
+        let nummer: Vec<_> = ku.blanketter.iter().map(|b| b.nummer).collect();
+        assert_eq!(nummer, vec![10, 11]);
+        assert_eq!(from_str(&to_string(&ku).unwrap()).unwrap(), ku);
+    }
+
+    #[test]
+    fn required_fields_must_be_set() {
+        assert!(matches!(
+            KU10Type::builder().inkomstar("2022").specifikationsnummer(1).build(),
+            Err(Error::MissingElement { missing, reading })
+                if missing == "InkomsttagareKU10" && reading == "KU10"
+        ));
+        assert!(matches!(
+            KontrolluppgiftBuilder::new().build(),
+            Err(Error::MissingElement { missing, .. }) if missing == "Avsandare"
+        ));
+    }
+}
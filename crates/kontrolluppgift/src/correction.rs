@@ -0,0 +1,132 @@
+use crate::form::KontrolluppgiftForm;
+use crate::meta::FieldMeta;
+use crate::value::FieldAccess;
+use crate::{Blankett, Kontrolluppgift, KontrolluppgiftType};
+
+/// The fields kept on a removal, the key of the kontrolluppgift and Borttag itself
+const BORTTAG_FIELDS: [&str; 5] = ["201", "203", "205", "215", "570"];
+
+/// Identifies the inkomsttagare on a removal when it has no identitetsbeteckning (215), the
+/// address is required as well then
+const BORTTAG_IDENTITET: [&str; 11] = [
+    "216", "217", "218", "219", "220", "221", "222", "224", "226", "228", "230",
+];
+
+/// Creates a file removing the Blanketter of a previously submitted file that the selector
+/// returns true for
+pub fn borttag<'a>(
+    kontrolluppgift: Kontrolluppgift<'a>,
+    selector: impl Fn(&Blankett<'a>) -> bool,
+) -> Kontrolluppgift<'a> {
+    Kontrolluppgift {
+        blanketter: kontrolluppgift
+            .blanketter
+            .into_iter()
+            .filter(|b| selector(b))
+            .map(|mut b| {
+                set_borttag(&mut b.blankettinnehall);
+                b
+            })
+            .collect(),
+        ..kontrolluppgift
+    }
+}
+
+/// Turns the form into a removal of itself, sets Borttag (205) and clears every field that is
+/// not part of its key
+pub fn set_borttag(ku: &mut KontrolluppgiftType) {
+    let has_identitetsbeteckning = ku.inkomsttagare().inkomsttagare().is_some();
+    let keep = |faltkod: &str| {
+        BORTTAG_FIELDS.contains(&faltkod)
+            || (!has_identitetsbeteckning && BORTTAG_IDENTITET.contains(&faltkod))
+    };
+    clear_fields(ku, ku.fields(), &keep);
+    *ku.borttag_mut() = Some(true);
+}
+
+fn clear_fields(
+    ku: &mut KontrolluppgiftType,
+    fields: &'static [FieldMeta],
+    keep: &dyn Fn(&str) -> bool,
+) {
+    for field in fields {
+        match (field.faltkod, field.children) {
+            (Some(faltkod), _) if !field.required && !keep(faltkod) => ku
+                .clear_by_code(faltkod)
+                .expect("every optional field of the form can be cleared"),
+            (None, Some(children)) => clear_fields(ku, children, keep),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::Validate;
+    use crate::value::Value;
+    use crate::{from_str, to_string};
+    use std::fs;
+
+    #[test]
+    fn removals_are_created_from_a_submitted_file() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        assert!(borttag(from_str(&xml).unwrap(), |_| false)
+            .blanketter
+            .is_empty());
+
+        let removal = borttag(from_str(&xml).unwrap(), |b| b.nummer == 2300);
+        let ku = &removal.blanketter[0].blankettinnehall;
+        assert!(ku.is_borttag());
+        assert_eq!(ku.get_by_code("011"), None);
+        assert_eq!(ku.get_by_code("093"), None);
+        assert_eq!(
+            ku.get_by_code("215"),
+            Some(Value::Str("195111232079".into()))
+        );
+        assert_eq!(ku.get_by_code("570"), Some(Value::Int(1)));
+        assert!(removal.validate().is_empty());
+        assert_eq!(from_str(&to_string(&removal).unwrap()).unwrap(), removal);
+    }
+
+    #[test]
+    fn removals_of_every_example_are_valid() {
+        for (name, xml) in crate::tests::example_files() {
+            let removal = borttag(from_str(&xml).unwrap(), |_| true);
+            let violations: Vec<_> = removal.validate().iter().map(|v| v.to_string()).collect();
+            assert!(violations.is_empty(), "{}: {:?}", name, violations);
+        }
+    }
+
+    #[test]
+    fn removals_without_identitetsbeteckning_keep_the_address() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        let form = &mut ku.blanketter[0].blankettinnehall;
+        form.clear_by_code("215").unwrap();
+        for (faltkod, value) in [
+            ("216", "Anna"),
+            ("217", "Svensson"),
+            ("222", "19511123"),
+            ("218", "Storgatan 3"),
+            ("219", "111 22"),
+            ("220", "Stockholm"),
+        ] {
+            form.set_by_code(faltkod, Value::Str(value.into())).unwrap();
+        }
+        assert!(ku.validate().is_empty());
+
+        let removal = borttag(ku, |_| true);
+        let ku = &removal.blanketter[0].blankettinnehall;
+        assert_eq!(ku.get_by_code("222"), Some(Value::Str("19511123".into())));
+        assert_eq!(ku.get_by_code("220"), Some(Value::Str("Stockholm".into())));
+        assert_eq!(ku.get_by_code("011"), None);
+        assert!(removal.validate().is_empty());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::correction::set_borttag;
+use crate::form::KontrolluppgiftForm;
+use crate::key::{check_duplicate_keys, BlankettKey};
+use crate::meta::FieldMeta;
+use crate::validation::Violation;
+use crate::value::{FieldAccess, Value};
+use crate::{Kontrolluppgift, KontrolluppgiftType};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// How a kontrolluppgift differs between two files, forms are matched by their [BlankettKey]
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlankettDiff {
+    Added(BlankettKey),
+    Removed(BlankettKey),
+    Changed {
+        key: BlankettKey,
+        fields: Vec<FieldDiff>,
+    },
+}
+
+/// A field that has a different value in the new file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    pub faltkod: &'static str,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// A file that can not be compared, as its forms are not identified by their keys
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffError {
+    /// Blanketter of the old file with the same key
+    OldDuplicates(Vec<Violation>),
+    /// Blanketter of the new file with the same key
+    NewDuplicates(Vec<Violation>),
+}
+
+impl Display for DiffError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (file, violations) = match self {
+            DiffError::OldDuplicates(violations) => ("old", violations),
+            DiffError::NewDuplicates(violations) => ("new", violations),
+        };
+        write!(f, "the {} file has duplicates: ", file)?;
+        let violations: Vec<_> = violations.iter().map(|v| v.to_string()).collect();
+        f.write_str(&violations.join(", "))
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+impl BlankettDiff {
+    pub fn key(&self) -> &BlankettKey {
+        match self {
+            BlankettDiff::Added(key) | BlankettDiff::Removed(key) => key,
+            BlankettDiff::Changed { key, .. } => key,
+        }
+    }
+}
+
+/// Compares the Blanketter of two files, forms that are equal in both are left out
+///
+/// Added and changed forms are listed in the order of the new file, followed by the removed forms
+/// in the order of the old file. A file with several forms with the same key is an error. Only
+/// the kontrolluppgifter are compared, the Arendeinformation of the Blanketter is ignored.
+pub fn diff(old: &Kontrolluppgift, new: &Kontrolluppgift) -> Result<Vec<BlankettDiff>, DiffError> {
+    let old_forms = by_key(old).map_err(DiffError::OldDuplicates)?;
+    let new_forms = by_key(new).map_err(DiffError::NewDuplicates)?;
+    let old_by_key: HashMap<_, _> = old_forms.iter().map(|(k, ku)| (k, *ku)).collect();
+    let mut diffs = vec![];
+    for (key, new_ku) in &new_forms {
+        match old_by_key.get(key) {
+            None => diffs.push(BlankettDiff::Added(key.clone())),
+            Some(old_ku) => {
+                let fields = diff_fields(old_ku, new_ku);
+                if !fields.is_empty() {
+                    diffs.push(BlankettDiff::Changed {
+                        key: key.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    let new_keys: HashSet<_> = new_forms.iter().map(|(k, _)| k).collect();
+    for (key, _) in &old_forms {
+        if !new_keys.contains(key) {
+            diffs.push(BlankettDiff::Removed(key.clone()));
+        }
+    }
+    Ok(diffs)
+}
+
+/// Creates the file correcting a submitted file to the new one, added and changed forms are sent
+/// in full and removed forms as Borttag, the Blanketter are numbered from 1
+pub fn correction<'a>(
+    old: Kontrolluppgift<'a>,
+    new: Kontrolluppgift<'a>,
+) -> Result<Kontrolluppgift<'a>, DiffError> {
+    let diffs = diff(&old, &new)?;
+    let send: HashSet<_> = diffs
+        .iter()
+        .filter(|d| !matches!(d, BlankettDiff::Removed(_)))
+        .map(|d| d.key())
+        .collect();
+    let remove: HashSet<_> = diffs
+        .iter()
+        .filter(|d| matches!(d, BlankettDiff::Removed(_)))
+        .map(|d| d.key())
+        .collect();
+
+    let added_or_changed = new
+        .blanketter
+        .into_iter()
+        .filter(|b| send.contains(&BlankettKey::from(&b.blankettinnehall)));
+    let removed = old
+        .blanketter
+        .into_iter()
+        .filter(|b| remove.contains(&BlankettKey::from(&b.blankettinnehall)))
+        .map(|mut b| {
+            set_borttag(&mut b.blankettinnehall);
+            b
+        });
+    Ok(Kontrolluppgift {
+        avsandare: new.avsandare,
+        blankettgemensamt: new.blankettgemensamt,
+        blanketter: added_or_changed
+            .chain(removed)
+            .zip(1..)
+            .map(|(mut b, nummer)| {
+                b.nummer = nummer;
+                b
+            })
+            .collect(),
+    })
+}
+
+/// The forms of the file with their keys, or the violations of the forms that repeat a key
+fn by_key<'k, 'a>(
+    kontrolluppgift: &'k Kontrolluppgift<'a>,
+) -> Result<Vec<(BlankettKey, &'k KontrolluppgiftType<'a>)>, Vec<Violation>> {
+    let mut violations = vec![];
+    check_duplicate_keys(&kontrolluppgift.blanketter, &mut violations);
+    if !violations.is_empty() {
+        return Err(violations);
+    }
+    Ok(kontrolluppgift
+        .blanketter
+        .iter()
+        .map(|b| (BlankettKey::from(&b.blankettinnehall), &b.blankettinnehall))
+        .collect())
+}
+
+fn diff_fields(old: &KontrolluppgiftType, new: &KontrolluppgiftType) -> Vec<FieldDiff> {
+    let mut faltkoder = vec![];
+    collect_faltkoder(new.fields(), &mut faltkoder);
+    faltkoder
+        .into_iter()
+        .filter_map(|faltkod| {
+            let (old, new) = (old.get_by_code(faltkod), new.get_by_code(faltkod));
+            (old != new).then_some(FieldDiff { faltkod, old, new })
+        })
+        .collect()
+}
+
+fn collect_faltkoder(fields: &'static [FieldMeta], faltkoder: &mut Vec<&'static str>) {
+    for field in fields {
+        match (field.faltkod, field.children) {
+            (Some(faltkod), _) => faltkoder.push(faltkod),
+            (None, Some(children)) => collect_faltkoder(children, faltkoder),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use std::fs;
+
+    #[test]
+    fn files_are_compared_by_key() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let with_specifikationsnummer = |specifikationsnummer| {
+            let mut ku = from_str(&xml).unwrap();
+            *ku.blanketter[0].blankettinnehall.specifikationsnummer_mut() = specifikationsnummer;
+            ku.blanketter.remove(0)
+        };
+        let mut old = from_str(&xml).unwrap();
+        old.blanketter.push(with_specifikationsnummer(2));
+        let mut new = from_str(&xml).unwrap();
+        new.blanketter[0]
+            .blankettinnehall
+            .set_by_code("011", Value::Int(340000))
+            .unwrap();
+        new.blanketter.push(with_specifikationsnummer(3));
+        assert!(diff(&old, &old).unwrap().is_empty());
+
+        let key = |specifikationsnummer| BlankettKey {
+            specifikationsnummer,
+            ..BlankettKey::from(&old.blanketter[0].blankettinnehall)
+        };
+        assert_eq!(
+            diff(&old, &new).unwrap(),
+            vec![
+                BlankettDiff::Changed {
+                    key: key(1),
+                    fields: vec![FieldDiff {
+                        faltkod: "011",
+                        old: Some(Value::Int(335000)),
+                        new: Some(Value::Int(340000)),
+                    }],
+                },
+                BlankettDiff::Added(key(3)),
+                BlankettDiff::Removed(key(2)),
+            ]
+        );
+
+        let mut twice = from_str(&xml).unwrap();
+        twice
+            .blanketter
+            .push(from_str(&xml).unwrap().blanketter.remove(0));
+        assert!(matches!(
+            diff(&twice, &new),
+            Err(DiffError::OldDuplicates(violations)) if violations.len() == 1
+        ));
+        assert!(matches!(
+            diff(&old, &twice),
+            Err(DiffError::NewDuplicates(violations)) if violations.len() == 1
+        ));
+
+        let correction = correction(old, new).unwrap();
+        let sent: Vec<_> = correction
+            .blanketter
+            .iter()
+            .map(|b| {
+                let ku = &b.blankettinnehall;
+                (b.nummer, ku.specifikationsnummer(), ku.is_borttag())
+            })
+            .collect();
+        assert_eq!(sent, vec![(1, 1, false), (2, 3, false), (3, 2, true)]);
+    }
+}
@@ -21,7 +21,15 @@ pub enum Error {
     EscapeError,
     UnknownPrefix(Vec<u8>),
     MissingElement { missing: String, reading: String },
+    OutOfSequence { element: String, reading: String },
+    TooManyElements { element: String, reading: String, max: usize },
     EmptyDocType,
+    /// There is no field with the faltkod on the element, when accessing fields by faltkod
+    UnknownFaltkod { element: String, faltkod: String },
+    /// The field can not be cleared as the element requires it
+    RequiredField { element: String, faltkod: String },
+    /// The value can not be set on the field, as it is of another kind or out of range
+    WrongValueKind { expected: String, found: String },
 }
 
 impl From<QXMLError> for Error {
@@ -81,7 +89,12 @@ impl fmt::Display for Error {
                 f.write_str("'")
             },
             Error::MissingElement { missing, reading }  => write!(f, "Missing element {}, while reading {}", missing, reading),
+            Error::OutOfSequence { element, reading } => write!(f, "Element {} is out of sequence order, while reading {}", element, reading),
+            Error::TooManyElements { element, reading, max } => write!(f, "At most {} {} allowed, while reading {}", max, element, reading),
             Error::EmptyDocType => write!(f, "DOCTYPE declaration must not be empty"),
+            Error::UnknownFaltkod { element, faltkod } => write!(f, "{} has no field with faltkod {}", element, faltkod),
+            Error::RequiredField { element, faltkod } => write!(f, "Field with faltkod {} is required on {}", faltkod, element),
+            Error::WrongValueKind { expected, found } => write!(f, "Expected {} value, got {}", expected, found),
         }
     }
 }
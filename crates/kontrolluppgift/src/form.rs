@@ -0,0 +1,266 @@
+use crate::inkomsttagare::Inkomsttagare;
+use crate::ku10::{KU10Type, UppgiftslamnareKU10};
+use crate::ku13::{KU13Type, UppgiftslamnareKU13};
+use crate::ku14::{KU14Type, UppgiftslamnareKU14};
+use crate::ku16::{KU16Type, UppgiftslamnareKU16};
+use crate::ku17::{KU17Type, UppgiftslamnareKU17};
+use crate::ku18::{KU18Type, UppgiftslamnareKU18};
+use crate::ku19::{KU19Type, UppgiftslamnareKU19};
+use crate::ku20::{KU20Type, UppgiftslamnareKU20};
+use crate::ku21::{KU21Type, UppgiftslamnareKU21};
+use crate::ku25::{KU25Type, UppgiftslamnareKU25};
+use crate::ku26::{KU26Type, UppgiftslamnareKU26};
+use crate::ku28::{KU28Type, UppgiftslamnareKU28};
+use crate::ku30::{KU30Type, UppgiftslamnareKU30};
+use crate::ku31::{KU31Type, UppgiftslamnareKU31};
+use crate::ku32::{KU32Type, UppgiftslamnareKU32};
+use crate::meta::{FieldMeta, Fields};
+use crate::{error, KontrolluppgiftType, Writable};
+use kontrolluppgift_macros::KUStringEnum;
+use std::borrow::Cow;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The kind of form a KontrolluppgiftType holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, KUStringEnum)]
+pub enum KontrolluppgiftKind {
+    KU10,
+    KU13,
+    KU14,
+    KU16,
+    KU17,
+    KU18,
+    KU19,
+    KU20,
+    KU21,
+    KU25,
+    KU26,
+    KU28,
+    KU30,
+    KU31,
+    KU32,
+}
+
+impl Display for KontrolluppgiftKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&Writable::get_str(self).unwrap())
+    }
+}
+
+/// The fields shared by the UppgiftslamnareKUxx structs of every form
+pub trait UppgiftslamnareKU<'a> {
+    fn uppgiftslamnar_id(&self) -> &str;
+    fn uppgiftslamnar_id_mut(&mut self) -> &mut Cow<'a, str>;
+    fn namn_uppgiftslamnare(&self) -> Option<&str>;
+    fn namn_uppgiftslamnare_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+}
+
+/// The fields shared by the KUxxType structs of every form
+pub trait KontrolluppgiftForm<'a> {
+    fn kind(&self) -> KontrolluppgiftKind;
+    fn inkomstar(&self) -> &str;
+    fn inkomstar_mut(&mut self) -> &mut Cow<'a, str>;
+    fn borttag(&self) -> Option<bool>;
+    fn borttag_mut(&mut self) -> &mut Option<bool>;
+    fn specifikationsnummer(&self) -> i32;
+    fn specifikationsnummer_mut(&mut self) -> &mut i32;
+    fn inkomsttagare(&self) -> &dyn Inkomsttagare<'a>;
+    fn inkomsttagare_mut(&mut self) -> &mut dyn Inkomsttagare<'a>;
+    fn uppgiftslamnare(&self) -> &dyn UppgiftslamnareKU<'a>;
+    fn uppgiftslamnare_mut(&mut self) -> &mut dyn UppgiftslamnareKU<'a>;
+    /// Every field of the form, as described by [Fields]
+    fn fields(&self) -> &'static [FieldMeta];
+
+    /// Whether this kontrolluppgift removes a previously submitted one
+    fn is_borttag(&self) -> bool {
+        self.borttag() == Some(true)
+    }
+}
+
+macro_rules! impl_form {
+    ($kind:ident, $ty:ident, $uppgiftslamnare:ident) => {
+        impl<'a> UppgiftslamnareKU<'a> for $uppgiftslamnare<'a> {
+            fn uppgiftslamnar_id(&self) -> &str {
+                &self.uppgiftslamnar_id
+            }
+            fn uppgiftslamnar_id_mut(&mut self) -> &mut Cow<'a, str> {
+                &mut self.uppgiftslamnar_id
+            }
+            fn namn_uppgiftslamnare(&self) -> Option<&str> {
+                self.namn_uppgiftslamnare.as_deref()
+            }
+            fn namn_uppgiftslamnare_mut(&mut self) -> &mut Option<Cow<'a, str>> {
+                &mut self.namn_uppgiftslamnare
+            }
+        }
+
+        impl<'a> KontrolluppgiftForm<'a> for $ty<'a> {
+            fn kind(&self) -> KontrolluppgiftKind {
+                KontrolluppgiftKind::$kind
+            }
+            fn inkomstar(&self) -> &str {
+                &self.inkomstar
+            }
+            fn inkomstar_mut(&mut self) -> &mut Cow<'a, str> {
+                &mut self.inkomstar
+            }
+            fn borttag(&self) -> Option<bool> {
+                self.borttag
+            }
+            fn borttag_mut(&mut self) -> &mut Option<bool> {
+                &mut self.borttag
+            }
+            fn specifikationsnummer(&self) -> i32 {
+                self.specifikationsnummer
+            }
+            fn specifikationsnummer_mut(&mut self) -> &mut i32 {
+                &mut self.specifikationsnummer
+            }
+            fn inkomsttagare(&self) -> &dyn Inkomsttagare<'a> {
+                &self.inkomsttagare
+            }
+            fn inkomsttagare_mut(&mut self) -> &mut dyn Inkomsttagare<'a> {
+                &mut self.inkomsttagare
+            }
+            fn uppgiftslamnare(&self) -> &dyn UppgiftslamnareKU<'a> {
+                &self.uppgiftslamnare
+            }
+            fn uppgiftslamnare_mut(&mut self) -> &mut dyn UppgiftslamnareKU<'a> {
+                &mut self.uppgiftslamnare
+            }
+            fn fields(&self) -> &'static [FieldMeta] {
+                <$ty as Fields>::FIELDS
+            }
+        }
+    };
+}
+
+impl_form!(KU10, KU10Type, UppgiftslamnareKU10);
+impl_form!(KU13, KU13Type, UppgiftslamnareKU13);
+impl_form!(KU14, KU14Type, UppgiftslamnareKU14);
+impl_form!(KU16, KU16Type, UppgiftslamnareKU16);
+impl_form!(KU17, KU17Type, UppgiftslamnareKU17);
+impl_form!(KU18, KU18Type, UppgiftslamnareKU18);
+impl_form!(KU19, KU19Type, UppgiftslamnareKU19);
+impl_form!(KU20, KU20Type, UppgiftslamnareKU20);
+impl_form!(KU21, KU21Type, UppgiftslamnareKU21);
+impl_form!(KU25, KU25Type, UppgiftslamnareKU25);
+impl_form!(KU26, KU26Type, UppgiftslamnareKU26);
+impl_form!(KU28, KU28Type, UppgiftslamnareKU28);
+impl_form!(KU30, KU30Type, UppgiftslamnareKU30);
+impl_form!(KU31, KU31Type, UppgiftslamnareKU31);
+impl_form!(KU32, KU32Type, UppgiftslamnareKU32);
+
+impl<'a> KontrolluppgiftType<'a> {
+    /// The form held by this variant
+    pub fn as_form(&self) -> &dyn KontrolluppgiftForm<'a> {
+        match self {
+            KontrolluppgiftType::KU10(ku) => ku,
+            KontrolluppgiftType::KU13(ku) => ku,
+            KontrolluppgiftType::KU14(ku) => ku,
+            KontrolluppgiftType::KU16(ku) => ku,
+            KontrolluppgiftType::KU17(ku) => ku,
+            KontrolluppgiftType::KU18(ku) => ku,
+            KontrolluppgiftType::KU19(ku) => ku,
+            KontrolluppgiftType::KU20(ku) => ku,
+            KontrolluppgiftType::KU21(ku) => ku,
+            KontrolluppgiftType::KU25(ku) => ku,
+            KontrolluppgiftType::KU26(ku) => ku,
+            KontrolluppgiftType::KU28(ku) => ku,
+            KontrolluppgiftType::KU30(ku) => ku,
+            KontrolluppgiftType::KU31(ku) => ku,
+            KontrolluppgiftType::KU32(ku) => ku,
+        }
+    }
+
+    /// The form held by this variant
+    pub fn as_form_mut(&mut self) -> &mut dyn KontrolluppgiftForm<'a> {
+        match self {
+            KontrolluppgiftType::KU10(ku) => ku,
+            KontrolluppgiftType::KU13(ku) => ku,
+            KontrolluppgiftType::KU14(ku) => ku,
+            KontrolluppgiftType::KU16(ku) => ku,
+            KontrolluppgiftType::KU17(ku) => ku,
+            KontrolluppgiftType::KU18(ku) => ku,
+            KontrolluppgiftType::KU19(ku) => ku,
+            KontrolluppgiftType::KU20(ku) => ku,
+            KontrolluppgiftType::KU21(ku) => ku,
+            KontrolluppgiftType::KU25(ku) => ku,
+            KontrolluppgiftType::KU26(ku) => ku,
+            KontrolluppgiftType::KU28(ku) => ku,
+            KontrolluppgiftType::KU30(ku) => ku,
+            KontrolluppgiftType::KU31(ku) => ku,
+            KontrolluppgiftType::KU32(ku) => ku,
+        }
+    }
+}
+
+impl<'a> KontrolluppgiftForm<'a> for KontrolluppgiftType<'a> {
+    fn kind(&self) -> KontrolluppgiftKind {
+        self.as_form().kind()
+    }
+    fn inkomstar(&self) -> &str {
+        self.as_form().inkomstar()
+    }
+    fn inkomstar_mut(&mut self) -> &mut Cow<'a, str> {
+        self.as_form_mut().inkomstar_mut()
+    }
+    fn borttag(&self) -> Option<bool> {
+        self.as_form().borttag()
+    }
+    fn borttag_mut(&mut self) -> &mut Option<bool> {
+        self.as_form_mut().borttag_mut()
+    }
+    fn specifikationsnummer(&self) -> i32 {
+        self.as_form().specifikationsnummer()
+    }
+    fn specifikationsnummer_mut(&mut self) -> &mut i32 {
+        self.as_form_mut().specifikationsnummer_mut()
+    }
+    fn inkomsttagare(&self) -> &dyn Inkomsttagare<'a> {
+        self.as_form().inkomsttagare()
+    }
+    fn inkomsttagare_mut(&mut self) -> &mut dyn Inkomsttagare<'a> {
+        self.as_form_mut().inkomsttagare_mut()
+    }
+    fn uppgiftslamnare(&self) -> &dyn UppgiftslamnareKU<'a> {
+        self.as_form().uppgiftslamnare()
+    }
+    fn uppgiftslamnare_mut(&mut self) -> &mut dyn UppgiftslamnareKU<'a> {
+        self.as_form_mut().uppgiftslamnare_mut()
+    }
+    fn fields(&self) -> &'static [FieldMeta] {
+        self.as_form().fields()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use std::fs;
+
+    #[test]
+    fn shared_fields_are_read_from_any_form() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+
+        let form = &mut ku.blanketter[0].blankettinnehall;
+        assert_eq!(form.kind(), KontrolluppgiftKind::KU10);
+        assert_eq!(form.kind().to_string(), "KU10");
+        assert_eq!(form.inkomstar(), "2022");
+        assert!(!form.is_borttag());
+
+        *form.borttag_mut() = Some(true);
+        *form.uppgiftslamnare_mut().namn_uppgiftslamnare_mut() = Some("Bolaget".into());
+        assert!(form.is_borttag());
+        assert_eq!(
+            form.uppgiftslamnare().namn_uppgiftslamnare(),
+            Some("Bolaget")
+        );
+        assert!(form.inkomsttagare().inkomsttagare().is_some());
+    }
+}
@@ -0,0 +1,744 @@
+use crate::ku10::InkomsttagareKU10;
+use crate::ku13::InkomsttagareKU13;
+use crate::ku14::InkomsttagareKU14;
+use crate::ku16::InkomsttagareKU16;
+use crate::ku17::InkomsttagareKU17;
+use crate::ku18::InkomsttagareKU18;
+use crate::ku19::InkomsttagareKU19;
+use crate::ku20::InkomsttagareKU20;
+use crate::ku21::InkomsttagareKU21;
+use crate::ku25::InkomsttagareKU25;
+use crate::ku26::InkomsttagareKU26;
+use crate::ku28::InkomsttagareKU28;
+use crate::ku30::InkomsttagareKU30;
+use crate::ku31::InkomsttagareKU31;
+use crate::ku32::InkomsttagareKU32;
+use crate::validation::{Validate, Violation};
+use crate::{IdentitetsbeteckningForPerson, Landskod};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// The longest Gatuadress, Gatuadress2 or Postort allowed
+pub const ADRESSRAD_MAX_LEN: usize = 35;
+/// The longest FriAdress allowed, three address lines
+pub const FRI_ADRESS_MAX_LEN: usize = 3 * ADRESSRAD_MAX_LEN;
+
+/// How an inkomsttagare is identified, the spec allows exactly one of these
+#[derive(Debug, Clone, PartialEq)]
+pub enum InkomsttagareIdentitet<'a> {
+    /// A personnummer, samordningsnummer or organisationsnummer in Inkomsttagare
+    Identitetsbeteckning(IdentitetsbeteckningForPerson<'a>),
+    /// A person without a Swedish identity, at least one of `fodelsetid` and `annat_id_nr` is set
+    Person {
+        fodelsetid: Option<Cow<'a, str>>,
+        annat_id_nr: Option<Cow<'a, str>>,
+        fornamn: Cow<'a, str>,
+        efternamn: Cow<'a, str>,
+    },
+    /// An organisation without a Swedish identity
+    Organisation {
+        org_namn: Cow<'a, str>,
+        annat_id_nr: Option<Cow<'a, str>>,
+    },
+}
+
+/// The fields shared by the InkomsttagareKUxx structs of every form.
+///
+/// Fields that only some forms have return `None` from the getter, and `None` from the `_mut`
+/// accessor, on the forms that lack them.
+pub trait Inkomsttagare<'a> {
+    fn inkomsttagare(&self) -> Option<&IdentitetsbeteckningForPerson<'a>>;
+    fn inkomsttagare_mut(&mut self) -> &mut Option<IdentitetsbeteckningForPerson<'a>>;
+    fn fornamn(&self) -> Option<&str>;
+    fn fornamn_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn efternamn(&self) -> Option<&str>;
+    fn efternamn_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn gatuadress(&self) -> Option<&str>;
+    fn gatuadress_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn postnummer(&self) -> Option<&str>;
+    fn postnummer_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn postort(&self) -> Option<&str>;
+    fn postort_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn landskod_postort(&self) -> Option<Landskod>;
+    fn landskod_postort_mut(&mut self) -> &mut Option<Landskod>;
+    fn fodelsetid(&self) -> Option<&str>;
+    fn fodelsetid_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn annat_id_nr(&self) -> Option<&str>;
+    fn annat_id_nr_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn org_namn(&self) -> Option<&str>;
+    fn org_namn_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn gatuadress2(&self) -> Option<&str>;
+    fn gatuadress2_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+    fn fri_adress(&self) -> Option<&str>;
+    fn fri_adress_mut(&mut self) -> &mut Option<Cow<'a, str>>;
+
+    fn landskod_tin(&self) -> Option<Landskod> {
+        None
+    }
+    fn landskod_tin_mut(&mut self) -> Option<&mut Option<Landskod>> {
+        None
+    }
+    fn tin(&self) -> Option<&str> {
+        None
+    }
+    fn tin_mut(&mut self) -> Option<&mut Option<Cow<'a, str>>> {
+        None
+    }
+    fn landskod_medborgare(&self) -> Option<Landskod> {
+        None
+    }
+    fn landskod_medborgare_mut(&mut self) -> Option<&mut Option<Landskod>> {
+        None
+    }
+    fn fodelseort(&self) -> Option<&str> {
+        None
+    }
+    fn fodelseort_mut(&mut self) -> Option<&mut Option<Cow<'a, str>>> {
+        None
+    }
+    fn landskod_fodelseort(&self) -> Option<Landskod> {
+        None
+    }
+    fn landskod_fodelseort_mut(&mut self) -> Option<&mut Option<Landskod>> {
+        None
+    }
+    fn landskod_hemvist(&self) -> Option<Landskod> {
+        None
+    }
+    fn landskod_hemvist_mut(&mut self) -> Option<&mut Option<Landskod>> {
+        None
+    }
+
+    /// The identification of the inkomsttagare, fails with every violated rule when the fields
+    /// do not make up exactly one of the ways the spec allows
+    fn identitet<'s>(&'s self) -> Result<InkomsttagareIdentitet<'s>, Vec<Violation>>
+    where
+        'a: 's,
+    {
+        let mut violations = vec![];
+        let mut not_allowed =
+            |faltkod: &'static str, field: &str, present: bool, with: (&'static str, &str)| {
+                if present {
+                    violations.push(Violation::new(
+                        &[with.0, faltkod],
+                        format!("{} is not allowed together with {}", field, with.1),
+                    ));
+                }
+            };
+        let identitet = if let Some(inkomsttagare) = self.inkomsttagare() {
+            let with = ("215", "Inkomsttagare");
+            not_allowed("222", "Fodelsetid", self.fodelsetid().is_some(), with);
+            not_allowed("224", "AnnatIDNr", self.annat_id_nr().is_some(), with);
+            not_allowed("226", "OrgNamn", self.org_namn().is_some(), with);
+            Some(InkomsttagareIdentitet::Identitetsbeteckning(
+                inkomsttagare.clone(),
+            ))
+        } else if let Some(org_namn) = self.org_namn() {
+            let with = ("226", "OrgNamn");
+            not_allowed("216", "Fornamn", self.fornamn().is_some(), with);
+            not_allowed("217", "Efternamn", self.efternamn().is_some(), with);
+            not_allowed("222", "Fodelsetid", self.fodelsetid().is_some(), with);
+            Some(InkomsttagareIdentitet::Organisation {
+                org_namn: org_namn.into(),
+                annat_id_nr: self.annat_id_nr().map(Cow::from),
+            })
+        } else if self.fodelsetid().is_some() || self.annat_id_nr().is_some() {
+            let id = match self.fodelsetid() {
+                Some(_) => ("222", "Fodelsetid"),
+                None => ("224", "AnnatIDNr"),
+            };
+            let mut required = |faltkod: &'static str, field: &str, present: bool| {
+                if !present {
+                    violations.push(Violation::new(
+                        &[id.0, faltkod],
+                        format!("{} is required together with {}", field, id.1),
+                    ));
+                }
+            };
+            required("216", "Fornamn", self.fornamn().is_some());
+            required("217", "Efternamn", self.efternamn().is_some());
+            self.fornamn()
+                .zip(self.efternamn())
+                .map(|(fornamn, efternamn)| InkomsttagareIdentitet::Person {
+                    fodelsetid: self.fodelsetid().map(Cow::from),
+                    annat_id_nr: self.annat_id_nr().map(Cow::from),
+                    fornamn: fornamn.into(),
+                    efternamn: efternamn.into(),
+                })
+        } else {
+            violations.push(Violation::new(
+                &["215", "222", "224", "226"],
+                "one of Inkomsttagare, Fodelsetid, AnnatIDNr or OrgNamn is required",
+            ));
+            None
+        };
+        match identitet {
+            Some(identitet) if violations.is_empty() => Ok(identitet),
+            _ => Err(violations),
+        }
+    }
+
+    /// Sets the fields identifying the inkomsttagare, clearing those of the other ways
+    fn set_identitet(&mut self, identitet: InkomsttagareIdentitet<'a>) {
+        *self.inkomsttagare_mut() = None;
+        *self.fodelsetid_mut() = None;
+        *self.annat_id_nr_mut() = None;
+        *self.org_namn_mut() = None;
+        match identitet {
+            InkomsttagareIdentitet::Identitetsbeteckning(inkomsttagare) => {
+                *self.inkomsttagare_mut() = Some(inkomsttagare);
+            }
+            InkomsttagareIdentitet::Person {
+                fodelsetid,
+                annat_id_nr,
+                fornamn,
+                efternamn,
+            } => {
+                *self.fodelsetid_mut() = fodelsetid;
+                *self.annat_id_nr_mut() = annat_id_nr;
+                *self.fornamn_mut() = Some(fornamn);
+                *self.efternamn_mut() = Some(efternamn);
+            }
+            InkomsttagareIdentitet::Organisation {
+                org_namn,
+                annat_id_nr,
+            } => {
+                *self.org_namn_mut() = Some(org_namn);
+                *self.annat_id_nr_mut() = annat_id_nr;
+                *self.fornamn_mut() = None;
+                *self.efternamn_mut() = None;
+            }
+        }
+    }
+
+    /// The address of the inkomsttagare, `None` when no address field is set. Fails with every
+    /// violated rule when the fields do not make up one of the kinds of [Adress]
+    fn adress<'s>(&'s self) -> Result<Option<Adress<'s>>, Vec<Violation>>
+    where
+        'a: 's,
+    {
+        let mut violations = vec![];
+        let mut check_len = |faltkod: &'static str, field: &str, value: Option<&str>, max| {
+            let len = value.map_or(0, |v| v.chars().count());
+            if len > max {
+                violations.push(Violation::new(
+                    &[faltkod],
+                    format!(
+                        "{} is {} characters, at most {} are allowed",
+                        field, len, max
+                    ),
+                ));
+            }
+        };
+        check_len("218", "Gatuadress", self.gatuadress(), ADRESSRAD_MAX_LEN);
+        check_len("228", "Gatuadress2", self.gatuadress2(), ADRESSRAD_MAX_LEN);
+        check_len("220", "Postort", self.postort(), ADRESSRAD_MAX_LEN);
+        check_len("230", "FriAdress", self.fri_adress(), FRI_ADRESS_MAX_LEN);
+
+        let structured = [
+            ("218", "Gatuadress", self.gatuadress()),
+            ("228", "Gatuadress2", self.gatuadress2()),
+            ("219", "Postnummer", self.postnummer()),
+            ("220", "Postort", self.postort()),
+        ];
+        let landskod_postort = self.landskod_postort();
+        let adress = if let Some(fri_adress) = self.fri_adress() {
+            for (faltkod, field, value) in structured {
+                if value.is_some() {
+                    violations.push(Violation::new(
+                        &["230", faltkod],
+                        format!("{} is not allowed together with FriAdress", field),
+                    ));
+                }
+            }
+            Adress::Fri {
+                fri_adress: fri_adress.into(),
+                landskod_postort,
+            }
+        } else if structured.iter().all(|(_, _, value)| value.is_none()) {
+            if landskod_postort.is_some() {
+                violations.push(Violation::new(
+                    &["221"],
+                    "LandskodPostort is not allowed without an address",
+                ));
+            }
+            return match violations.is_empty() {
+                true => Ok(None),
+                false => Err(violations),
+            };
+        } else {
+            for (faltkod, field, value) in [structured[0], structured[3]] {
+                if value.is_none() {
+                    violations.push(Violation::new(
+                        &[faltkod],
+                        format!("{} is required in a structured address", field),
+                    ));
+                }
+            }
+            let gatuadress = self.gatuadress().unwrap_or_default().into();
+            let gatuadress2 = self.gatuadress2().map(Cow::from);
+            let postort = self.postort().unwrap_or_default().into();
+            match landskod_postort {
+                None | Some(Landskod::SE) => {
+                    match self.postnummer() {
+                        None => violations.push(Violation::new(
+                            &["219"],
+                            "Postnummer is required in a Swedish address",
+                        )),
+                        Some(postnummer) if !is_svenskt_postnummer(postnummer) => {
+                            violations.push(Violation::new(
+                                &["219", "221"],
+                                format!(
+                                    "{} is not a Swedish postnummer, LandskodPostort is required for foreign addresses",
+                                    postnummer
+                                ),
+                            ))
+                        }
+                        Some(_) => {}
+                    }
+                    Adress::Svensk {
+                        gatuadress,
+                        gatuadress2,
+                        postnummer: self.postnummer().unwrap_or_default().into(),
+                        postort,
+                    }
+                }
+                Some(landskod_postort) => Adress::Utlandsk {
+                    gatuadress,
+                    gatuadress2,
+                    postnummer: self.postnummer().map(Cow::from),
+                    postort,
+                    landskod_postort,
+                },
+            }
+        };
+        match violations.is_empty() {
+            true => Ok(Some(adress)),
+            false => Err(violations),
+        }
+    }
+
+    /// Sets the address fields, clearing those the address does not use
+    fn set_adress(&mut self, adress: Option<Adress<'a>>) {
+        *self.gatuadress_mut() = None;
+        *self.gatuadress2_mut() = None;
+        *self.postnummer_mut() = None;
+        *self.postort_mut() = None;
+        *self.landskod_postort_mut() = None;
+        *self.fri_adress_mut() = None;
+        match adress {
+            None => {}
+            Some(Adress::Svensk {
+                gatuadress,
+                gatuadress2,
+                postnummer,
+                postort,
+            }) => {
+                *self.gatuadress_mut() = Some(gatuadress);
+                *self.gatuadress2_mut() = gatuadress2;
+                *self.postnummer_mut() = Some(postnummer);
+                *self.postort_mut() = Some(postort);
+            }
+            Some(Adress::Utlandsk {
+                gatuadress,
+                gatuadress2,
+                postnummer,
+                postort,
+                landskod_postort,
+            }) => {
+                *self.gatuadress_mut() = Some(gatuadress);
+                *self.gatuadress2_mut() = gatuadress2;
+                *self.postnummer_mut() = postnummer;
+                *self.postort_mut() = Some(postort);
+                *self.landskod_postort_mut() = Some(landskod_postort);
+            }
+            Some(Adress::Fri {
+                fri_adress,
+                landskod_postort,
+            }) => {
+                *self.fri_adress_mut() = Some(fri_adress);
+                *self.landskod_postort_mut() = landskod_postort;
+            }
+        }
+    }
+
+    /// Copies every field the two structs have in common into a new `T`,
+    /// fields `T` does not have are dropped
+    fn convert<T>(&self) -> T
+    where
+        Self: Sized,
+        T: Inkomsttagare<'a> + Default,
+    {
+        fn owned<'a>(value: Option<&str>) -> Option<Cow<'a, str>> {
+            value.map(|v| Cow::Owned(v.to_string()))
+        }
+
+        let mut to = T::default();
+        *to.inkomsttagare_mut() = self.inkomsttagare().cloned();
+        *to.fornamn_mut() = owned(self.fornamn());
+        *to.efternamn_mut() = owned(self.efternamn());
+        *to.gatuadress_mut() = owned(self.gatuadress());
+        *to.postnummer_mut() = owned(self.postnummer());
+        *to.postort_mut() = owned(self.postort());
+        *to.landskod_postort_mut() = self.landskod_postort();
+        *to.fodelsetid_mut() = owned(self.fodelsetid());
+        *to.annat_id_nr_mut() = owned(self.annat_id_nr());
+        *to.org_namn_mut() = owned(self.org_namn());
+        *to.gatuadress2_mut() = owned(self.gatuadress2());
+        *to.fri_adress_mut() = owned(self.fri_adress());
+        if let Some(field) = to.landskod_tin_mut() {
+            *field = self.landskod_tin();
+        }
+        if let Some(field) = to.tin_mut() {
+            *field = owned(self.tin());
+        }
+        if let Some(field) = to.landskod_medborgare_mut() {
+            *field = self.landskod_medborgare();
+        }
+        if let Some(field) = to.fodelseort_mut() {
+            *field = owned(self.fodelseort());
+        }
+        if let Some(field) = to.landskod_fodelseort_mut() {
+            *field = self.landskod_fodelseort();
+        }
+        if let Some(field) = to.landskod_hemvist_mut() {
+            *field = self.landskod_hemvist();
+        }
+        to
+    }
+}
+
+/// The identification, address and TIN rules shared by every InkomsttagareKUxx
+pub(crate) fn validate_inkomsttagare(inkomsttagare: &dyn Inkomsttagare) -> Vec<Violation> {
+    let mut violations = inkomsttagare.identitet().err().unwrap_or_default();
+    match inkomsttagare.adress() {
+        Ok(None) if inkomsttagare.inkomsttagare().is_none() => violations.push(Violation::new(
+            &["215", "218", "220", "230"],
+            "an address is required when Inkomsttagare is not present",
+        )),
+        Ok(_) => {}
+        Err(adress) => violations.extend(adress),
+    }
+    let tin = match (inkomsttagare.landskod_tin(), inkomsttagare.tin()) {
+        (Some(_), None) => Some("TIN is required when LandskodTIN is present"),
+        (None, Some(_)) => Some("LandskodTIN is required when TIN is present"),
+        _ => None,
+    };
+    violations.extend(tin.map(|message| Violation::new(&["076", "252"], message)));
+    violations
+}
+
+/// The address of an inkomsttagare, either structured or free-form
+#[derive(Debug, Clone, PartialEq)]
+pub enum Adress<'a> {
+    /// An address in Sweden, without LandskodPostort
+    Svensk {
+        gatuadress: Cow<'a, str>,
+        gatuadress2: Option<Cow<'a, str>>,
+        /// Five digits, optionally with a space after the third
+        postnummer: Cow<'a, str>,
+        postort: Cow<'a, str>,
+    },
+    /// An address outside of Sweden
+    Utlandsk {
+        gatuadress: Cow<'a, str>,
+        gatuadress2: Option<Cow<'a, str>>,
+        postnummer: Option<Cow<'a, str>>,
+        postort: Cow<'a, str>,
+        landskod_postort: Landskod,
+    },
+    /// An address that does not fit the structured fields, in FriAdress
+    Fri {
+        fri_adress: Cow<'a, str>,
+        landskod_postort: Option<Landskod>,
+    },
+}
+
+fn is_svenskt_postnummer(postnummer: &str) -> bool {
+    static POSTNUMMER_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^[0-9]{3} ?[0-9]{2}$").expect("These are constructed and should be valid")
+    });
+    POSTNUMMER_REGEX.is_match(postnummer)
+}
+
+macro_rules! impl_inkomsttagare {
+    ($ty:ident $(, $optional:ident)*) => {
+        impl<'a> Inkomsttagare<'a> for $ty<'a> {
+            fn inkomsttagare(&self) -> Option<&IdentitetsbeteckningForPerson<'a>> {
+                self.inkomsttagare.as_ref()
+            }
+            fn inkomsttagare_mut(&mut self) -> &mut Option<IdentitetsbeteckningForPerson<'a>> {
+                &mut self.inkomsttagare
+            }
+            fn landskod_postort(&self) -> Option<Landskod> {
+                self.landskod_postort
+            }
+            fn landskod_postort_mut(&mut self) -> &mut Option<Landskod> {
+                &mut self.landskod_postort
+            }
+            impl_inkomsttagare!(@str fornamn, fornamn_mut);
+            impl_inkomsttagare!(@str efternamn, efternamn_mut);
+            impl_inkomsttagare!(@str gatuadress, gatuadress_mut);
+            impl_inkomsttagare!(@str postnummer, postnummer_mut);
+            impl_inkomsttagare!(@str postort, postort_mut);
+            impl_inkomsttagare!(@str fodelsetid, fodelsetid_mut);
+            impl_inkomsttagare!(@str annat_id_nr, annat_id_nr_mut);
+            impl_inkomsttagare!(@str org_namn, org_namn_mut);
+            impl_inkomsttagare!(@str gatuadress2, gatuadress2_mut);
+            impl_inkomsttagare!(@str fri_adress, fri_adress_mut);
+            $(impl_inkomsttagare!(@optional $optional);)*
+        }
+
+        impl Validate for $ty<'_> {
+            fn validate(&self) -> Vec<Violation> {
+                validate_inkomsttagare(self)
+            }
+        }
+    };
+    (@str $field:ident, $field_mut:ident) => {
+        fn $field(&self) -> Option<&str> {
+            self.$field.as_deref()
+        }
+        fn $field_mut(&mut self) -> &mut Option<Cow<'a, str>> {
+            &mut self.$field
+        }
+    };
+    (@optional tin) => {
+        fn tin(&self) -> Option<&str> {
+            self.tin.as_deref()
+        }
+        fn tin_mut(&mut self) -> Option<&mut Option<Cow<'a, str>>> {
+            Some(&mut self.tin)
+        }
+    };
+    (@optional fodelseort) => {
+        fn fodelseort(&self) -> Option<&str> {
+            self.fodelseort.as_deref()
+        }
+        fn fodelseort_mut(&mut self) -> Option<&mut Option<Cow<'a, str>>> {
+            Some(&mut self.fodelseort)
+        }
+    };
+    (@optional landskod_tin) => {
+        impl_inkomsttagare!(@landskod landskod_tin, landskod_tin_mut);
+    };
+    (@optional landskod_medborgare) => {
+        impl_inkomsttagare!(@landskod landskod_medborgare, landskod_medborgare_mut);
+    };
+    (@optional landskod_fodelseort) => {
+        impl_inkomsttagare!(@landskod landskod_fodelseort, landskod_fodelseort_mut);
+    };
+    (@optional landskod_hemvist) => {
+        impl_inkomsttagare!(@landskod landskod_hemvist, landskod_hemvist_mut);
+    };
+    (@landskod $field:ident, $field_mut:ident) => {
+        fn $field(&self) -> Option<Landskod> {
+            self.$field
+        }
+        fn $field_mut(&mut self) -> Option<&mut Option<Landskod>> {
+            Some(&mut self.$field)
+        }
+    };
+}
+
+impl_inkomsttagare!(InkomsttagareKU10, landskod_tin, tin);
+impl_inkomsttagare!(InkomsttagareKU13, landskod_tin, tin, landskod_medborgare);
+impl_inkomsttagare!(InkomsttagareKU14, landskod_tin, tin, landskod_medborgare);
+impl_inkomsttagare!(InkomsttagareKU16, landskod_tin, tin);
+impl_inkomsttagare!(InkomsttagareKU17, landskod_tin, tin, landskod_medborgare);
+impl_inkomsttagare!(InkomsttagareKU18, landskod_tin, tin);
+impl_inkomsttagare!(InkomsttagareKU19, landskod_tin, tin, landskod_medborgare);
+impl_inkomsttagare!(
+    InkomsttagareKU20,
+    landskod_tin,
+    tin,
+    fodelseort,
+    landskod_fodelseort
+);
+impl_inkomsttagare!(
+    InkomsttagareKU21,
+    landskod_tin,
+    tin,
+    fodelseort,
+    landskod_fodelseort
+);
+impl_inkomsttagare!(InkomsttagareKU25);
+impl_inkomsttagare!(InkomsttagareKU26);
+impl_inkomsttagare!(InkomsttagareKU28, landskod_tin, tin);
+impl_inkomsttagare!(InkomsttagareKU30);
+impl_inkomsttagare!(
+    InkomsttagareKU31,
+    landskod_tin,
+    tin,
+    fodelseort,
+    landskod_fodelseort,
+    landskod_hemvist
+);
+impl_inkomsttagare!(InkomsttagareKU32, landskod_tin, tin);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trim_adress<'a>(inkomsttagare: &mut dyn Inkomsttagare<'a>) {
+        if let Some(value) = inkomsttagare.gatuadress_mut() {
+            *value = Cow::Owned(value.trim().to_string());
+        }
+    }
+
+    #[test]
+    fn inkomsttagare_is_converted_between_forms() {
+        let ku31 = InkomsttagareKU31 {
+            inkomsttagare: Some("191612299279".try_into().unwrap()),
+            fornamn: Some("Test".into()),
+            efternamn: Some("Testsson".into()),
+            landskod_postort: Some(Landskod::FI),
+            landskod_tin: Some(Landskod::FI),
+            tin: Some("123".into()),
+            fodelseort: Some("Helsingfors".into()),
+            landskod_hemvist: Some(Landskod::FI),
+            ..Default::default()
+        };
+
+        let ku20: InkomsttagareKU20 = ku31.convert();
+        assert_eq!(ku20.inkomsttagare, ku31.inkomsttagare);
+        assert_eq!(ku20.fornamn.as_deref(), Some("Test"));
+        assert_eq!(ku20.landskod_tin, Some(Landskod::FI));
+        assert_eq!(ku20.fodelseort.as_deref(), Some("Helsingfors"));
+
+        let ku25: InkomsttagareKU25 = ku31.convert();
+        assert_eq!(ku25.efternamn.as_deref(), Some("Testsson"));
+        assert_eq!(ku25.landskod_tin(), None);
+        assert_eq!(ku25.tin(), None);
+
+        let mut with_adress = InkomsttagareKU10 {
+            gatuadress: Some("  Storgatan 1 ".into()),
+            ..Default::default()
+        };
+        trim_adress(&mut with_adress);
+        assert_eq!(with_adress.gatuadress(), Some("Storgatan 1"));
+    }
+
+    #[test]
+    fn adress_is_structured_or_free() {
+        let mut ku20 = InkomsttagareKU20::default();
+        assert_eq!(ku20.adress(), Ok(None));
+
+        let svensk = Adress::Svensk {
+            gatuadress: "Storgatan 1".into(),
+            gatuadress2: None,
+            postnummer: "621 45".into(),
+            postort: "Visby".into(),
+        };
+        ku20.set_adress(Some(svensk.clone()));
+        assert_eq!(ku20.adress(), Ok(Some(svensk)));
+
+        ku20.postnummer = Some("SW1A 1AA".into());
+        ku20.postort = None;
+        let messages: Vec<_> = ku20
+            .adress()
+            .unwrap_err()
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Postort is required in a structured address",
+                "SW1A 1AA is not a Swedish postnummer, LandskodPostort is required for foreign addresses"
+            ]
+        );
+
+        ku20.postort = Some("London".into());
+        ku20.landskod_postort = Some(Landskod::GB);
+        assert!(matches!(
+            ku20.adress(),
+            Ok(Some(Adress::Utlandsk {
+                landskod_postort: Landskod::GB,
+                ..
+            }))
+        ));
+
+        ku20.fri_adress = Some("Storgatan 1, Visby".into());
+        assert_eq!(ku20.adress().unwrap_err().len(), 3);
+
+        ku20.set_adress(Some(Adress::Fri {
+            fri_adress: "x".repeat(FRI_ADRESS_MAX_LEN + 1).into(),
+            landskod_postort: None,
+        }));
+        assert_eq!(ku20.gatuadress, None);
+        assert_eq!(
+            ku20.adress().unwrap_err()[0].message,
+            "FriAdress is 106 characters, at most 105 are allowed"
+        );
+    }
+
+    #[test]
+    fn exactly_one_identitet_is_allowed() {
+        let mut ku10 = InkomsttagareKU10::default();
+        assert_eq!(
+            ku10.validate()[0].message,
+            "one of Inkomsttagare, Fodelsetid, AnnatIDNr or OrgNamn is required"
+        );
+
+        let person = InkomsttagareIdentitet::Person {
+            fodelsetid: Some("19800101".into()),
+            annat_id_nr: None,
+            fornamn: "Test".into(),
+            efternamn: "Testsson".into(),
+        };
+        ku10.set_identitet(person.clone());
+        assert_eq!(ku10.identitet(), Ok(person));
+        assert_eq!(
+            ku10.validate()[0].message,
+            "an address is required when Inkomsttagare is not present"
+        );
+        ku10.set_adress(Some(Adress::Utlandsk {
+            gatuadress: "Storgatan 1".into(),
+            gatuadress2: None,
+            postnummer: None,
+            postort: "Oslo".into(),
+            landskod_postort: Landskod::NO,
+        }));
+        assert!(ku10.validate().is_empty());
+
+        ku10.efternamn = None;
+        ku10.org_namn = Some("Organisation".into());
+        let messages: Vec<_> = ku10.validate().into_iter().map(|v| v.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Fornamn is not allowed together with OrgNamn",
+                "Fodelsetid is not allowed together with OrgNamn"
+            ]
+        );
+
+        ku10.set_identitet(InkomsttagareIdentitet::Identitetsbeteckning(
+            "191612299279".try_into().unwrap(),
+        ));
+        assert!(ku10.validate().is_empty());
+        ku10.annat_id_nr = Some("123".into());
+        ku10.tin = Some("123".into());
+        let messages: Vec<_> = ku10.validate().into_iter().map(|v| v.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "AnnatIDNr is not allowed together with Inkomsttagare",
+                "LandskodTIN is required when TIN is present"
+            ]
+        );
+
+        ku10.set_identitet(InkomsttagareIdentitet::Person {
+            fodelsetid: None,
+            annat_id_nr: Some("123".into()),
+            fornamn: "Test".into(),
+            efternamn: "Testsson".into(),
+        });
+        ku10.fornamn = None;
+        assert_eq!(
+            ku10.identitet().unwrap_err()[0].message,
+            "Fornamn is required together with AnnatIDNr"
+        );
+    }
+}
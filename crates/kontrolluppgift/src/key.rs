@@ -0,0 +1,180 @@
+use crate::form::{KontrolluppgiftForm, KontrolluppgiftKind};
+use crate::validation::Violation;
+use crate::{Blankett, KontrolluppgiftType};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The fields Skatteverket identifies a kontrolluppgift by, two forms with the same key in one
+/// file are rejected
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlankettKey {
+    pub kind: KontrolluppgiftKind,
+    pub uppgiftslamnar_id: String,
+    pub inkomsttagare: InkomsttagareKey,
+    pub inkomstar: String,
+    pub specifikationsnummer: i32,
+}
+
+/// Inkomsttagare (215), or the Fodelsetid, AnnatIDNr, names and OrgNamn that are set when the
+/// inkomsttagare has no identitetsbeteckning
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InkomsttagareKey {
+    pub inkomsttagare: Option<String>,
+    pub fodelsetid: Option<String>,
+    pub annat_id_nr: Option<String>,
+    pub fornamn: Option<String>,
+    pub efternamn: Option<String>,
+    pub org_namn: Option<String>,
+}
+
+impl From<&KontrolluppgiftType<'_>> for BlankettKey {
+    fn from(ku: &KontrolluppgiftType<'_>) -> Self {
+        let inkomsttagare = ku.inkomsttagare();
+        let inkomsttagare = match inkomsttagare.inkomsttagare() {
+            Some(id) => InkomsttagareKey {
+                inkomsttagare: Some(String::from(id)),
+                ..Default::default()
+            },
+            None => InkomsttagareKey {
+                inkomsttagare: None,
+                fodelsetid: inkomsttagare.fodelsetid().map(String::from),
+                annat_id_nr: inkomsttagare.annat_id_nr().map(String::from),
+                fornamn: inkomsttagare.fornamn().map(String::from),
+                efternamn: inkomsttagare.efternamn().map(String::from),
+                org_namn: inkomsttagare.org_namn().map(String::from),
+            },
+        };
+        BlankettKey {
+            kind: ku.kind(),
+            uppgiftslamnar_id: ku.uppgiftslamnare().uppgiftslamnar_id().to_string(),
+            inkomsttagare,
+            inkomstar: ku.inkomstar().to_string(),
+            specifikationsnummer: ku.specifikationsnummer(),
+        }
+    }
+}
+
+impl Display for InkomsttagareKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let parts: Vec<_> = [
+            &self.inkomsttagare,
+            &self.fodelsetid,
+            &self.annat_id_nr,
+            &self.fornamn,
+            &self.efternamn,
+            &self.org_namn,
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+        f.write_str(&parts.join(" "))
+    }
+}
+
+impl Display for BlankettKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.kind,
+            self.uppgiftslamnar_id,
+            self.inkomsttagare,
+            self.inkomstar,
+            self.specifikationsnummer
+        )
+    }
+}
+
+/// Adds a violation for every Blankett that repeats the nummer or the key of an earlier one
+pub(crate) fn check_duplicates(blanketter: &[Blankett], violations: &mut Vec<Violation>) {
+    let mut nummer = HashSet::new();
+    for b in blanketter {
+        if !nummer.insert(b.nummer) {
+            violations.push(Violation {
+                blankett: Some(b.nummer),
+                ..Violation::new(&[], "the nummer is used by another Blankett")
+            });
+        }
+    }
+    check_duplicate_keys(blanketter, violations);
+}
+
+/// Adds a violation for every Blankett that repeats the key of an earlier one, the earlier one is
+/// referred to by its index as the nummer may be repeated as well
+pub(crate) fn check_duplicate_keys(blanketter: &[Blankett], violations: &mut Vec<Violation>) {
+    let mut keys = HashMap::new();
+    for (index, b) in blanketter.iter().enumerate() {
+        let key = BlankettKey::from(&b.blankettinnehall);
+        if let Some(first) = keys.get(&key) {
+            violations.push(Violation {
+                blankett: Some(b.nummer),
+                ..Violation::new(
+                    &["201", "215", "203", "570"],
+                    format!(
+                        "{} has the same key as the Blankett at index {}",
+                        key.kind, first
+                    ),
+                )
+            });
+        } else {
+            keys.insert(key, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use crate::validation::Validate;
+    use std::fs;
+
+    #[test]
+    fn duplicate_blanketter_are_reported() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        let key = BlankettKey::from(&ku.blanketter[0].blankettinnehall);
+        assert_eq!(key.to_string(), "KU10 165021680250 195111232079 2022 1");
+
+        let copy = from_str(&xml).unwrap().blanketter.remove(0);
+        ku.blanketter.push(copy);
+        let mut copy = from_str(&xml).unwrap().blanketter.remove(0);
+        copy.nummer = 2301;
+        *copy.blankettinnehall.specifikationsnummer_mut() = 2;
+        ku.blanketter.push(copy);
+
+        let violations: Vec<_> = ku.validate().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "Blankett 2300: the nummer is used by another Blankett",
+                "Blankett 2300: [201, 215, 203, 570] KU10 has the same key as the Blankett at index 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_parts_of_a_name_are_not_joined() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let with_name = |fornamn: &'static str, efternamn: &'static str| {
+            let mut b = from_str(&xml).unwrap().blanketter.remove(0);
+            let inkomsttagare = b.blankettinnehall.inkomsttagare_mut();
+            *inkomsttagare.inkomsttagare_mut() = None;
+            *inkomsttagare.fornamn_mut() = Some(fornamn.into());
+            *inkomsttagare.efternamn_mut() = Some(efternamn.into());
+            BlankettKey::from(&b.blankettinnehall)
+        };
+        let first = with_name("Anna Maria", "Svensson");
+        let second = with_name("Anna", "Maria Svensson");
+        assert_ne!(first, second);
+        assert_eq!(first.inkomsttagare.to_string(), "Anna Maria Svensson");
+    }
+}
@@ -1,112 +1,213 @@
+use crate::validation::{employment_rules, Validate, Violation};
 use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 10
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU10"))]
 pub struct KU10Type<'a> {
-    #[ku(name(b"KontantBruttolonMm"), code("011"))]
+    #[ku(
+        name(b"KontantBruttolonMm"),
+        code("011"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub kontant_bruttolon_mm: Option<i32>,
-    #[ku(name(b"FormanUtomBilDrivmedel"), code("012"))]
+    #[ku(
+        name(b"FormanUtomBilDrivmedel"),
+        code("012"),
+        amount(true),
+        range(0, 999999999),
+        seq(2)
+    )]
     pub forman_utom_bil_drivmedel: Option<i32>,
-    #[ku(name(b"BilformanUtomDrivmedel"), code("013"))]
+    #[ku(
+        name(b"BilformanUtomDrivmedel"),
+        code("013"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub bilforman_utom_drivmedel: Option<i32>,
-    #[ku(name(b"DrivmedelVidBilforman"), code("018"))]
+    #[ku(
+        name(b"DrivmedelVidBilforman"),
+        code("018"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub drivmedel_vid_bilforman: Option<i32>,
-    #[ku(name(b"AndraKostnadsers"), code("020"))]
+    #[ku(
+        name(b"AndraKostnadsers"),
+        code("020"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub andra_kostnadsers: Option<i32>,
-    #[ku(name(b"UnderlagRutarbete"), code("021"))]
+    #[ku(
+        name(b"UnderlagRutarbete"),
+        code("021"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub underlag_rutarbete: Option<i32>,
-    #[ku(name(b"UnderlagRotarbete"), code("022"))]
+    #[ku(
+        name(b"UnderlagRotarbete"),
+        code("022"),
+        amount(true),
+        range(0, 999999999),
+        seq(7)
+    )]
     pub underlag_rotarbete: Option<i32>,
-    #[ku(name(b"ErsMEgenavgifter"), code("025"))]
+    #[ku(
+        name(b"ErsMEgenavgifter"),
+        code("025"),
+        amount(true),
+        range(0, 999999999),
+        seq(8)
+    )]
     pub ers_m_egenavgifter: Option<i32>,
-    #[ku(name(b"Tjanstepension"), code("030"))]
+    #[ku(
+        name(b"Tjanstepension"),
+        code("030"),
+        amount(true),
+        range(0, 999999999),
+        seq(9)
+    )]
     pub tjanstepension: Option<i32>,
-    #[ku(name(b"ErsEjSocAvg"), code("031"))]
+    #[ku(
+        name(b"ErsEjSocAvg"),
+        code("031"),
+        amount(true),
+        range(0, 999999999),
+        seq(10)
+    )]
     pub ers_ej_soc_avg: Option<i32>,
-    #[ku(name(b"ErsEjSocAvgEjJobbavd"), code("032"))]
+    #[ku(
+        name(b"ErsEjSocAvgEjJobbavd"),
+        code("032"),
+        amount(true),
+        range(0, 999999999),
+        seq(11)
+    )]
     pub ers_ej_soc_avg_ej_jobbavd: Option<i32>,
-    #[ku(name(b"Forskarskattenamnden"), code("035"))]
+    #[ku(
+        name(b"Forskarskattenamnden"),
+        code("035"),
+        amount(true),
+        range(0, 999999999),
+        seq(12)
+    )]
     pub forsarskattenamnden: Option<i32>,
-    #[ku(name(b"VissaAvdrag"), code("037"))]
+    #[ku(
+        name(b"VissaAvdrag"),
+        code("037"),
+        amount(true),
+        range(0, 999999999),
+        seq(13)
+    )]
     pub vissa_avdrag: Option<i32>,
-    #[ku(name(b"Hyresersattning"), code("039"))]
+    #[ku(
+        name(b"Hyresersattning"),
+        code("039"),
+        amount(true),
+        range(0, 999999999),
+        seq(14)
+    )]
     pub hyresersattning: Option<i32>,
-    #[ku(name(b"BostadSmahus"), code("041"))]
+    #[ku(name(b"BostadSmahus"), code("041"), seq(15))]
     pub bostad_smahus: Option<bool>,
-    #[ku(name(b"BostadEjSmahus"), code("043"))]
+    #[ku(name(b"BostadEjSmahus"), code("043"), seq(16))]
     pub bostad_ej_smahus: Option<bool>,
-    #[ku(name(b"FormanHarJusterats"), code("048"))]
+    #[ku(name(b"FormanHarJusterats"), code("048"), seq(17))]
     pub forman_har_justerats: Option<bool>,
-    #[ku(name(b"FormanSomPension"), code("049"))]
+    #[ku(name(b"FormanSomPension"), code("049"), seq(18))]
     pub forman_som_pension: Option<bool>,
-    #[ku(name(b"Bilersattning"), code("050"))]
+    #[ku(name(b"Bilersattning"), code("050"), seq(19))]
     pub bilersattning: Option<bool>,
-    #[ku(name(b"Traktamente"), code("051"))]
+    #[ku(name(b"Traktamente"), code("051"), seq(20))]
     pub traktamente: Option<bool>,
-    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"))]
+    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"), seq(21))]
     pub personaloption_forvarv_andel: Option<bool>,
-    #[ku(name(b"Arbetsstallenummer"), code("060"))]
+    #[ku(name(b"Arbetsstallenummer"), code("060"), seq(22))]
     pub arbetsstallenummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(23))]
     pub delagare: Option<bool>,
-    #[ku(name(b"SocialAvgiftsAvtal"), code("093"))]
+    #[ku(name(b"SocialAvgiftsAvtal"), code("093"), seq(24))]
     pub social_avgifts_avtal: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(25)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(26))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(27))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU10"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU10"), required(true), inner_ty(true), seq(28))]
     pub inkomsttagare: InkomsttagareKU10<'a>,
-    #[ku(name(b"UppgiftslamnareKU10"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU10"), required(true), inner_ty(true), seq(29))]
     pub uppgiftslamnare: UppgiftslamnareKU10<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU10Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        employment_rules(&mut violations, self);
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU10"))]
 pub struct UppgiftslamnareKU10<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU10"))]
 pub struct InkomsttagareKU10<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(2))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(3))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(4))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(5))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(6))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(7))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(8))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(9))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(10))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(11))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(12))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(13))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(14))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -127,9 +228,9 @@ mod tests {
         )
         .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -208,7 +309,34 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku10).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku10, re_parsed);
     }
+
+    #[test]
+    fn ku10_business_rules_are_validated() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut parsed = from_str(&xml).unwrap();
+        let KU10(ku10) = &mut parsed.blanketter[0].blankettinnehall else {
+            panic!("expected a KU10")
+        };
+        assert!(ku10.validate().is_empty());
+
+        ku10.bostad_smahus = Some(true);
+        ku10.bostad_ej_smahus = Some(true);
+        ku10.drivmedel_vid_bilforman = Some(1200);
+        ku10.borttag = Some(true);
+        let violations: Vec<_> = ku10.validate().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "[041, 043] BostadSmahus and BostadEjSmahus must not both be present (see KU10 faltkod 041)",
+                "[018, 013] BilformanUtomDrivmedel is required when DrivmedelVidBilforman is present (see KU10 faltkod 018)",
+                "[205, 011, 018] KontantBruttolonMm, DrivmedelVidBilforman must not be present when Borttag is set (see KU10 faltkod 205)",
+            ]
+        );
+    }
 }
@@ -1,94 +1,153 @@
+use crate::validation::{employment_rules, Validate, Violation};
 use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 13
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU13"))]
 pub struct KU13Type<'a> {
-    #[ku(name(b"KontantBruttolonMm"), code("011"))]
+    #[ku(
+        name(b"KontantBruttolonMm"),
+        code("011"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub kontant_bruttolon_mm: Option<i32>,
-    #[ku(name(b"FormanUtomBilDrivmedel"), code("012"))]
+    #[ku(
+        name(b"FormanUtomBilDrivmedel"),
+        code("012"),
+        amount(true),
+        range(0, 999999999),
+        seq(2)
+    )]
     pub forman_utom_bil_drivmedel: Option<i32>,
-    #[ku(name(b"BilformanUtomDrivmedel"), code("013"))]
+    #[ku(
+        name(b"BilformanUtomDrivmedel"),
+        code("013"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub bilforman_utom_drivmedel: Option<i32>,
-    #[ku(name(b"DrivmedelVidBilforman"), code("018"))]
+    #[ku(
+        name(b"DrivmedelVidBilforman"),
+        code("018"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub drivmedel_vid_bilforman: Option<i32>,
-    #[ku(name(b"Tjanstepension"), code("030"))]
+    #[ku(
+        name(b"Tjanstepension"),
+        code("030"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub tjanstepension: Option<i32>,
-    #[ku(name(b"ErsEjSocAvg"), code("031"))]
+    #[ku(
+        name(b"ErsEjSocAvg"),
+        code("031"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub ers_ej_soc_avg: Option<i32>,
-    #[ku(name(b"ErsFormanBostadMmSINK"), code("036"))]
+    #[ku(
+        name(b"ErsFormanBostadMmSINK"),
+        code("036"),
+        amount(true),
+        range(0, 999999999),
+        seq(7)
+    )]
     pub ers_forman_bostad_mm_sink: Option<i32>,
-    #[ku(name(b"BostadSmahus"), code("041"))]
+    #[ku(name(b"BostadSmahus"), code("041"), seq(8))]
     pub bostad_smahus: Option<bool>,
-    #[ku(name(b"BostadEjSmahus"), code("043"))]
+    #[ku(name(b"BostadEjSmahus"), code("043"), seq(9))]
     pub bostad_ej_smahus: Option<bool>,
-    #[ku(name(b"FormanHarJusterats"), code("048"))]
+    #[ku(name(b"FormanHarJusterats"), code("048"), seq(10))]
     pub forman_har_justerats: Option<bool>,
-    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"))]
+    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"), seq(11))]
     pub personaloption_forvarv_andel: Option<bool>,
-    #[ku(name(b"Arbetsstallenummer"), code("060"))]
+    #[ku(name(b"Arbetsstallenummer"), code("060"), seq(12))]
     pub arbetsstallenummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(13))]
     pub delagare: Option<bool>,
-    #[ku(name(b"SocialAvgiftsAvtal"), code("093"))]
+    #[ku(name(b"SocialAvgiftsAvtal"), code("093"), seq(14))]
     pub social_avgifts_avtal: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(15)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(16))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(17))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU13"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU13"), required(true), inner_ty(true), seq(18))]
     pub inkomsttagare: InkomsttagareKU13<'a>,
-    #[ku(name(b"UppgiftslamnareKU13"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU13"), required(true), inner_ty(true), seq(19))]
     pub uppgiftslamnare: UppgiftslamnareKU13<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU13Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        employment_rules(&mut violations, self);
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU13"))]
 pub struct UppgiftslamnareKU13<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU13"))]
 pub struct InkomsttagareKU13<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"LandskodMedborgare"), code("081"))]
+    #[ku(name(b"LandskodMedborgare"), code("081"), seq(2))]
     pub landskod_medborgare: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(3))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(4))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(5))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(6))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(7))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(8))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(9))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(10))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(11))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(12))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(13))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(14))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(15))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -167,7 +226,7 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku13).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku13, re_parsed);
     }
 }
@@ -1,114 +1,199 @@
+use crate::validation::{both_or_neither, employment_rules, requires, Validate, Violation};
 use crate::{error, IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KUStringEnum, KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KUStringEnum, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 14
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU14"))]
 pub struct KU14Type<'a> {
-    #[ku(name(b"KontantBruttolonMm"), code("011"))]
+    #[ku(
+        name(b"KontantBruttolonMm"),
+        code("011"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub kontant_bruttolon_mm: Option<i32>,
-    #[ku(name(b"FormanUtomBilDrivmedel"), code("012"))]
+    #[ku(
+        name(b"FormanUtomBilDrivmedel"),
+        code("012"),
+        amount(true),
+        range(0, 999999999),
+        seq(2)
+    )]
     pub forman_utom_bil_drivmedel: Option<i32>,
-    #[ku(name(b"BilformanUtomDrivmedel"), code("013"))]
+    #[ku(
+        name(b"BilformanUtomDrivmedel"),
+        code("013"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub bilforman_utom_drivmedel: Option<i32>,
-    #[ku(name(b"DrivmedelVidBilforman"), code("018"))]
+    #[ku(
+        name(b"DrivmedelVidBilforman"),
+        code("018"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub drivmedel_vid_bilforman: Option<i32>,
-    #[ku(name(b"AndraKostnadsers"), code("020"))]
+    #[ku(
+        name(b"AndraKostnadsers"),
+        code("020"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub andra_kostnadsers: Option<i32>,
-    #[ku(name(b"UnderlagRutarbete"), code("021"))]
+    #[ku(
+        name(b"UnderlagRutarbete"),
+        code("021"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub underlag_rutarbete: Option<i32>,
-    #[ku(name(b"UnderlagRotarbete"), code("022"))]
+    #[ku(
+        name(b"UnderlagRotarbete"),
+        code("022"),
+        amount(true),
+        range(0, 999999999),
+        seq(7)
+    )]
     pub underlag_rotarbete: Option<i32>,
-    #[ku(name(b"ErsMEgenavgifter"), code("025"))]
+    #[ku(
+        name(b"ErsMEgenavgifter"),
+        code("025"),
+        amount(true),
+        range(0, 999999999),
+        seq(8)
+    )]
     pub ers_m_egenavgifter: Option<i32>,
-    #[ku(name(b"Tjanstepension"), code("030"))]
+    #[ku(
+        name(b"Tjanstepension"),
+        code("030"),
+        amount(true),
+        range(0, 999999999),
+        seq(9)
+    )]
     pub tjanstepension: Option<i32>,
-    #[ku(name(b"ErsEjSocAvg"), code("031"))]
+    #[ku(
+        name(b"ErsEjSocAvg"),
+        code("031"),
+        amount(true),
+        range(0, 999999999),
+        seq(10)
+    )]
     pub ers_ej_soc_avg: Option<i32>,
-    #[ku(name(b"Forskarskattenamnden"), code("035"))]
+    #[ku(
+        name(b"Forskarskattenamnden"),
+        code("035"),
+        amount(true),
+        range(0, 999999999),
+        seq(11)
+    )]
     pub forsarskattenamnden: Option<i32>,
-    #[ku(name(b"BostadSmahus"), code("041"))]
+    #[ku(name(b"BostadSmahus"), code("041"), seq(12))]
     pub bostad_smahus: Option<bool>,
-    #[ku(name(b"BostadEjSmahus"), code("043"))]
+    #[ku(name(b"BostadEjSmahus"), code("043"), seq(13))]
     pub bostad_ej_smahus: Option<bool>,
-    #[ku(name(b"FormanHarJusterats"), code("048"))]
+    #[ku(name(b"FormanHarJusterats"), code("048"), seq(14))]
     pub forman_har_justerats: Option<bool>,
-    #[ku(name(b"FormanSomPension"), code("049"))]
+    #[ku(name(b"FormanSomPension"), code("049"), seq(15))]
     pub forman_som_pension: Option<bool>,
-    #[ku(name(b"Bilersattning"), code("050"))]
+    #[ku(name(b"Bilersattning"), code("050"), seq(16))]
     pub bilersattning: Option<bool>,
-    #[ku(name(b"Traktamente"), code("051"))]
+    #[ku(name(b"Traktamente"), code("051"), seq(17))]
     pub traktamente: Option<bool>,
-    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"))]
+    #[ku(name(b"PersonaloptionForvarvAndel"), code("059"), seq(18))]
     pub personaloption_forvarv_andel: Option<bool>,
-    #[ku(name(b"Arbetsstallenummer"), code("060"))]
+    #[ku(name(b"Arbetsstallenummer"), code("060"), seq(19))]
     pub arbetsstallenummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(20))]
     pub delagare: Option<bool>,
-    #[ku(name(b"LandskodArbetsland"), code("090"))]
+    #[ku(name(b"LandskodArbetsland"), code("090"), seq(21))]
     pub landskod_arbetsland: Option<Cow<'a, str>>,
-    #[ku(name(b"UtsandUnderTid"), code("091"))]
+    #[ku(name(b"UtsandUnderTid"), code("091"), seq(22))]
     pub utsand_under_tid: Option<KU14UtsandUnderTid>,
-    #[ku(name(b"Kategori"), code("092"))]
+    #[ku(name(b"Kategori"), code("092"), seq(23))]
     pub kategori: Option<KU14Kategori>,
-    #[ku(name(b"SocialAvgiftsAvtal"), code("093"))]
+    #[ku(name(b"SocialAvgiftsAvtal"), code("093"), seq(24))]
     pub social_avgifts_avtal: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(25)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(26))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(27))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU14"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU14"), required(true), inner_ty(true), seq(28))]
     pub inkomsttagare: InkomsttagareKU14<'a>,
-    #[ku(name(b"UppgiftslamnareKU14"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU14"), required(true), inner_ty(true), seq(29))]
     pub uppgiftslamnare: UppgiftslamnareKU14<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU14Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        employment_rules(&mut violations, self);
+        both_or_neither(&mut violations, self, "090", "091");
+        requires(&mut violations, self, "092", "091");
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU14"))]
 pub struct UppgiftslamnareKU14<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU14"))]
 pub struct InkomsttagareKU14<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"LandskodMedborgare"), code("081"))]
+    #[ku(name(b"LandskodMedborgare"), code("081"), seq(2))]
     pub landskod_medborgare: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(3))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(4))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(5))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(6))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(7))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(8))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(9))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(10))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(11))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(12))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(13))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(14))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(15))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -214,7 +299,32 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku14).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku14, re_parsed);
     }
+
+    #[test]
+    fn ku14_utsandning_must_be_consistent() {
+        let mut ku14 = KU14Type::builder()
+            .inkomstar("2022")
+            .specifikationsnummer(1)
+            .inkomsttagare(InkomsttagareKU14::default())
+            .uppgiftslamnare(
+                UppgiftslamnareKU14::builder()
+                    .uppgiftslamnar_id("165599990602")
+                    .build()
+                    .unwrap(),
+            )
+            .landskod_arbetsland("FI")
+            .kategori(KU14Kategori::B)
+            .build()
+            .unwrap();
+        let violations = ku14.validate();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].faltkoder, vec!["090", "091"]);
+        assert_eq!(violations[1].faltkoder, vec!["092", "091"]);
+
+        ku14.utsand_under_tid = Some(KU14UtsandUnderTid::A);
+        assert!(ku14.validate().is_empty());
+    }
 }
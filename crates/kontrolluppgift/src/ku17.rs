@@ -1,86 +1,121 @@
+use crate::validation::{sjoinkomst_rules, Validate, Violation};
 use crate::{IdentitetsbeteckningForPerson, Landskod, NarfartFjarrfart};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 17
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU17"))]
 pub struct KU17Type<'a> {
-    #[ku(name(b"KontantBruttolonMm"), code("011"))]
+    #[ku(
+        name(b"KontantBruttolonMm"),
+        code("011"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub kontant_bruttolon_mm: Option<i32>,
-    #[ku(name(b"FormanUtomBilDrivmedel"), code("012"))]
+    #[ku(
+        name(b"FormanUtomBilDrivmedel"),
+        code("012"),
+        amount(true),
+        range(0, 999999999),
+        seq(2)
+    )]
     pub forman_utom_bil_drivmedel: Option<i32>,
-    #[ku(name(b"Fartygssignal"), code("026"))]
+    #[ku(name(b"Fartygssignal"), code("026"), pattern("[A-Z0-9]{3,7}"), seq(3))]
     pub fartygssignal: Option<Cow<'a, str>>,
-    #[ku(name(b"AntalDagarSjoinkomst"), code("027"))]
-    pub antal_dagar_sjoinkomst: Option<i32>, // todo restrict to 0-366 days
-    #[ku(name(b"NarfartFjarrfart"), code("028"))]
+    #[ku(name(b"AntalDagarSjoinkomst"), code("027"), range(0, 366), seq(4))]
+    pub antal_dagar_sjoinkomst: Option<i32>,
+    #[ku(name(b"NarfartFjarrfart"), code("028"), seq(5))]
     pub narfart_fjarrfart: Option<NarfartFjarrfart>,
-    #[ku(name(b"ErsEjSocAvg"), code("031"))]
+    #[ku(
+        name(b"ErsEjSocAvg"),
+        code("031"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub ers_ej_soc_avg: Option<i32>,
-    #[ku(name(b"Arbetsstallenummer"), code("060"))]
+    #[ku(name(b"Arbetsstallenummer"), code("060"), seq(7))]
     pub arbetsstallenummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(8))]
     pub delagare: Option<bool>,
-    #[ku(name(b"SocialAvgiftsAvtal"), code("093"))]
+    #[ku(name(b"SocialAvgiftsAvtal"), code("093"), seq(9))]
     pub social_avgifts_avtal: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(10)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(11))]
     pub borttag: Option<bool>,
-    #[ku(name(b"FartygetsNamn"), code("223"))]
+    #[ku(name(b"FartygetsNamn"), code("223"), seq(12))]
     pub fartygets_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(13))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU17"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU17"), required(true), inner_ty(true), seq(14))]
     pub inkomsttagare: InkomsttagareKU17<'a>,
-    #[ku(name(b"UppgiftslamnareKU17"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU17"), required(true), inner_ty(true), seq(15))]
     pub uppgiftslamnare: UppgiftslamnareKU17<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU17Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        sjoinkomst_rules(&mut violations, self);
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU17"))]
 pub struct UppgiftslamnareKU17<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU17"))]
 pub struct InkomsttagareKU17<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"LandskodMedborgare"), code("081"))]
+    #[ku(name(b"LandskodMedborgare"), code("081"), seq(2))]
     pub landskod_medborgare: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(3))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(4))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(5))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(6))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(7))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(8))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(9))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(10))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(11))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(12))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(13))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(14))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(15))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -154,7 +189,7 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku17).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku17, re_parsed);
     }
 }
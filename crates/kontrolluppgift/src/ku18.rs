@@ -1,71 +1,99 @@
-use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{both_or_neither, Validate, Violation};
+use crate::{Ersattningskod, IdentitetsbeteckningForPerson, Landskod};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 18
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU18"))]
 pub struct KU18Type<'a> {
-    #[ku(name(b"AvdragenSkatt"), code("001"))]
+    #[ku(
+        name(b"AvdragenSkatt"),
+        code("001"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub avdragen_skatt: Option<i32>,
-    #[ku(name(b"Ersattningskod"), code("004"))]
-    pub ersattningskod: Option<Cow<'a, str>>,
-    // this can be an enum
-    #[ku(name(b"ErsattningBelopp"), code("005"))]
+    #[ku(name(b"Ersattningskod"), code("004"), seq(2))]
+    pub ersattningskod: Option<Ersattningskod>,
+    #[ku(
+        name(b"ErsattningBelopp"),
+        code("005"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub ersattning_belopp: Option<i32>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(4)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(5))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(6))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU18"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU18"), required(true), inner_ty(true), seq(7))]
     pub inkomsttagare: InkomsttagareKU18<'a>,
-    #[ku(name(b"UppgiftslamnareKU18"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU18"), required(true), inner_ty(true), seq(8))]
     pub uppgiftslamnare: UppgiftslamnareKU18<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU18Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        both_or_neither(&mut violations, self, "004", "005");
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU18"))]
 pub struct UppgiftslamnareKU18<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU18"))]
 pub struct InkomsttagareKU18<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(2))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(3))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(4))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(5))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(6))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(7))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(8))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(9))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(10))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(11))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(12))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(13))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(14))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -102,7 +130,7 @@ mod tests {
                 },
                 blankettinnehall: KU18(KU18Type {
                     avdragen_skatt: Some(1),
-                    ersattningskod: Some("405".into()),
+                    ersattningskod: Some(Ersattningskod::Avtalsgruppsjukforsakring),
                     ersattning_belopp: Some(19),
                     inkomstar: "2022".into(),
                     borttag: Some(false),
@@ -131,7 +159,45 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku18).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku18, re_parsed);
     }
+
+    #[test]
+    fn ku18_ersattningskod_requires_belopp() {
+        let mut ku18 = KU18Type {
+            avdragen_skatt: None,
+            ersattningskod: Some(Ersattningskod::PrivatPension),
+            ersattning_belopp: None,
+            inkomstar: "2022".into(),
+            borttag: None,
+            specifikationsnummer: 1,
+            inkomsttagare: InkomsttagareKU18 {
+                landskod_tin: None,
+                inkomsttagare: Some("191612299279".try_into().unwrap()),
+                fornamn: None,
+                efternamn: None,
+                gatuadress: None,
+                postnummer: None,
+                postort: None,
+                landskod_postort: None,
+                fodelsetid: None,
+                annat_id_nr: None,
+                org_namn: None,
+                gatuadress2: None,
+                fri_adress: None,
+                tin: None,
+            },
+            uppgiftslamnare: UppgiftslamnareKU18 {
+                uppgiftslamnar_id: "165599990602".into(),
+                namn_uppgiftslamnare: None,
+            },
+        };
+        let violations = ku18.validate();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].faltkoder, vec!["004", "005"]);
+
+        ku18.ersattning_belopp = Some(100);
+        assert!(ku18.validate().is_empty());
+    }
 }
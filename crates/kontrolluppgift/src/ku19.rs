@@ -1,73 +1,101 @@
-use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{both_or_neither, Validate, Violation};
+use crate::{Ersattningskod, IdentitetsbeteckningForPerson, Landskod};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 extern crate self as kontrolluppgift;
 
 /// Kontrolluppgift 19
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU19"))]
 pub struct KU19Type<'a> {
-    #[ku(name(b"AvdragenSkatt"), code("001"))]
+    #[ku(
+        name(b"AvdragenSkatt"),
+        code("001"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub avdragen_skatt: Option<i32>,
-    // this can be an enum
-    #[ku(name(b"Ersattningskod"), code("004"))]
-    pub ersattningskod: Option<Cow<'a, str>>,
-    #[ku(name(b"ErsattningBelopp"), code("005"))]
+    #[ku(name(b"Ersattningskod"), code("004"), seq(2))]
+    pub ersattningskod: Option<Ersattningskod>,
+    #[ku(
+        name(b"ErsattningBelopp"),
+        code("005"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub ersattning_belopp: Option<i32>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(4)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(5))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(6))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU19"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU19"), required(true), inner_ty(true), seq(7))]
     pub inkomsttagare: InkomsttagareKU19<'a>,
-    #[ku(name(b"UppgiftslamnareKU19"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU19"), required(true), inner_ty(true), seq(8))]
     pub uppgiftslamnare: UppgiftslamnareKU19<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU19Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        both_or_neither(&mut violations, self, "004", "005");
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU19"))]
 pub struct UppgiftslamnareKU19<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU19"))]
 pub struct InkomsttagareKU19<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"LandskodMedborgare"), code("081"))]
+    #[ku(name(b"LandskodMedborgare"), code("081"), seq(2))]
     pub landskod_medborgare: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(3))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(4))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(5))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(6))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(7))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(8))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(9))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(10))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(11))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(12))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(13))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(14))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(15))]
     pub tin: Option<Cow<'a, str>>,
 }
 
@@ -104,7 +132,7 @@ mod tests {
                 },
                 blankettinnehall: KU19(KU19Type {
                     avdragen_skatt: Some(1),
-                    ersattningskod: Some("402".into()),
+                    ersattningskod: Some(Ersattningskod::Tjanstepension),
                     ersattning_belopp: Some(19),
                     inkomstar: "2022".into(),
                     borttag: Some(false),
@@ -134,7 +162,7 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku19).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku19, re_parsed);
     }
 }
@@ -1,78 +1,119 @@
+use crate::validation::{no_amounts_when_set, Validate, Violation};
 use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 /// Kontrolluppgift 20
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU20"))]
 pub struct KU20Type<'a> {
-    #[ku(name(b"AvdragenSkatt"), code("001"))]
+    #[ku(
+        name(b"AvdragenSkatt"),
+        code("001"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub avdragen_skatt: Option<i32>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(2))]
     pub delagare: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(3)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(4))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Ranteinkomst"), code("500"))]
+    #[ku(
+        name(b"Ranteinkomst"),
+        code("500"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub ranteinkomst: Option<i32>,
-    #[ku(name(b"Forfogarkonto"), code("502"))]
+    #[ku(name(b"Forfogarkonto"), code("502"), seq(6))]
     pub forfogarkonto: Option<bool>,
-    #[ku(name(b"RanteinkomstEjKonto"), code("503"))]
+    #[ku(
+        name(b"RanteinkomstEjKonto"),
+        code("503"),
+        amount(true),
+        range(0, 999999999),
+        seq(7)
+    )]
     pub ranteinkomst_ej_konto: Option<i32>,
-    #[ku(name(b"AnnanInkomst"), code("504"))]
+    #[ku(
+        name(b"AnnanInkomst"),
+        code("504"),
+        amount(true),
+        range(0, 999999999),
+        seq(8)
+    )]
     pub annan_inkomst: Option<i32>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(9))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU20"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU20"), required(true), inner_ty(true), seq(10))]
     pub inkomsttagare: InkomsttagareKU20<'a>,
-    #[ku(name(b"UppgiftslamnareKU20"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU20"), required(true), inner_ty(true), seq(11))]
     pub uppgiftslamnare: UppgiftslamnareKU20<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU20Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        no_amounts_when_set(&mut violations, self, "205", &[]);
+        violations
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU20"))]
 pub struct InkomsttagareKU20<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Fodelseort"), code("077"))]
+    #[ku(name(b"Fodelseort"), code("077"), seq(2))]
     pub fodelseort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodFodelseort"), code("078"))]
+    #[ku(name(b"LandskodFodelseort"), code("078"), seq(3))]
     pub landskod_fodelseort: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(4))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(5))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(6))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(7))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(8))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(9))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(10))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(11))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(12))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(13))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(14))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(15))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(16))]
     pub tin: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU20"))]
 pub struct UppgiftslamnareKU20<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -93,9 +134,9 @@ mod tests {
         )
         .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -157,7 +198,7 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku20).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku20, re_parsed);
     }
 }
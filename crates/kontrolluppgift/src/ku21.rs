@@ -1,89 +1,131 @@
-use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{requires, vardepapper_rules, Validate, Violation};
+use crate::{IdentitetsbeteckningForPerson, Isin, Landskod};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 /// Kontrolluppgift 21
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU21"))]
 pub struct KU21Type<'a> {
-    #[ku(name(b"AvdragenSkatt"), code("001"))]
+    #[ku(
+        name(b"AvdragenSkatt"),
+        code("001"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub avdragen_skatt: Option<i32>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(2)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(3))]
     pub borttag: Option<bool>,
-    #[ku(name(b"AnnanInkomst"), code("504"))]
+    #[ku(
+        name(b"AnnanInkomst"),
+        code("504"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub annan_inkomst: Option<i32>,
-    #[ku(name(b"RantaFordringsratter"), code("520"))]
+    #[ku(
+        name(b"RantaFordringsratter"),
+        code("520"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub ranta_fordringsratter: Option<i32>,
-    #[ku(name(b"UtbetaltIVissaFall"), code("522"))]
+    #[ku(
+        name(b"UtbetaltIVissaFall"),
+        code("522"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub utbetalt_i_vissa_fall: Option<i32>,
-    #[ku(name(b"Depanummer"), code("523"))]
+    #[ku(name(b"Depanummer"), code("523"), seq(7))]
     pub depanummer: Option<i32>,
-    #[ku(name(b"AndelAvDepan"), code("524"))]
+    #[ku(name(b"AndelAvDepan"), code("524"), seq(8))]
     pub andel_av_depan: Option<f32>,
-    #[ku(name(b"ErhallenRantekompensation"), code("525"))]
+    #[ku(name(b"ErhallenRantekompensation"), code("525"), amount(true), seq(9))]
     pub erhallen_rantekompensation: Option<f32>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(10))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"VPNamn"), code("571"))]
+    #[ku(name(b"VPNamn"), code("571"), seq(11))]
     pub vp_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"ISIN"), code("572"))]
-    pub isin: Option<Cow<'a, str>>,
-    #[ku(name(b"AvyttradTillISK"), code("573"))]
+    #[ku(name(b"ISIN"), code("572"), seq(12))]
+    pub isin: Option<Isin<'a>>,
+    #[ku(name(b"AvyttradTillISK"), code("573"), seq(13))]
     pub avyttrad_till_isk: Option<bool>,
-    #[ku(name(b"OkandVarde"), code("599"))]
+    #[ku(name(b"OkandVarde"), code("599"), seq(14))]
     pub okand_varde: Option<bool>,
 
-    #[ku(name(b"InkomsttagareKU21"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU21"), required(true), inner_ty(true), seq(15))]
     pub inkomsttagare: InkomsttagareKU21<'a>,
-    #[ku(name(b"UppgiftslamnareKU21"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU21"), required(true), inner_ty(true), seq(16))]
     pub uppgiftslamnare: UppgiftslamnareKU21<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU21Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        vardepapper_rules(&mut violations, self);
+        requires(&mut violations, self, "573", "520");
+        violations
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU21"))]
 pub struct InkomsttagareKU21<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Fodelseort"), code("077"))]
+    #[ku(name(b"Fodelseort"), code("077"), seq(2))]
     pub fodelseort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodFodelseort"), code("078"))]
+    #[ku(name(b"LandskodFodelseort"), code("078"), seq(3))]
     pub landskod_fodelseort: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(4))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(5))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(6))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(7))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(8))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(9))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(10))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(11))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(12))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(13))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(14))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(15))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(16))]
     pub tin: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU21"))]
 pub struct UppgiftslamnareKU21<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -104,9 +146,9 @@ mod tests {
         )
         .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -144,7 +186,7 @@ mod tests {
                     erhallen_rantekompensation: Some(9.0),
                     specifikationsnummer: 5,
                     vp_namn: Some("vp namn".into()),
-                    isin: Some("isin".into()),
+                    isin: Some("SE0000108656".try_into().unwrap()),
                     avyttrad_till_isk: Some(false),
                     okand_varde: Some(true),
                     inkomsttagare: InkomsttagareKU21 {
@@ -173,7 +215,64 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku21).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku21, re_parsed);
     }
+
+    #[test]
+    fn ku21_business_rules_are_validated() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFTER RÄNTA, UTDELNING M.M. KU21 FÖR_2022.xml",
+        )
+        .unwrap();
+        let mut parsed = from_str(&xml).unwrap();
+        assert!(parsed.blanketter[0].blankettinnehall.validate().is_empty());
+        let KU21(ku21) = &mut parsed.blanketter[0].blankettinnehall else {
+            panic!("expected a KU21")
+        };
+
+        ku21.isin = None;
+        ku21.andel_av_depan = Some(0.5);
+        ku21.okand_varde = Some(true);
+        let violations: Vec<_> = ku21.validate().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec![
+                "[524, 523] Depanummer is required when AndelAvDepan is present (see KU21 faltkod 524)",
+                "[571, 572] one of VPNamn or ISIN is required (see KU21 faltkod 571)",
+                "[599, 504, 520] AnnanInkomst, RantaFordringsratter must not be present when OkandVarde is set (see KU21 faltkod 599)",
+            ]
+        );
+
+        ku21.andel_av_depan = None;
+        ku21.okand_varde = None;
+        ku21.vp_namn = None;
+        ku21.borttag = Some(true);
+        let violations: Vec<_> = ku21.validate().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec!["[205, 001, 504, 520] AvdragenSkatt, AnnanInkomst, RantaFordringsratter must not be present when Borttag is set (see KU21 faltkod 205)"]
+        );
+    }
+
+    #[test]
+    fn ku21_avyttrad_till_isk_requires_ranta() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFTER RÄNTA, UTDELNING M.M. KU21 FÖR_2022.xml",
+        )
+        .unwrap();
+        let mut parsed = from_str(&xml).unwrap();
+        let KU21(ku21) = &mut parsed.blanketter[0].blankettinnehall else {
+            panic!("expected a KU21")
+        };
+        ku21.avyttrad_till_isk = Some(true);
+        assert!(ku21.validate().is_empty());
+
+        ku21.ranta_fordringsratter = None;
+        let violations: Vec<_> = ku21.validate().iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            violations,
+            vec!["[573, 520] RantaFordringsratter is required when AvyttradTillISK is present (see KU21 faltkod 573)"]
+        );
+    }
 }
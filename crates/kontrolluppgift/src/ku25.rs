@@ -1,68 +1,94 @@
 use std::borrow::Cow;
 
 use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU25"))]
 pub struct KU25Type<'a> {
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(1))]
     pub delagare: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(2)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(3))]
     pub borttag: Option<bool>,
-    #[ku(name(b"AvdragsgillRanta"), code("540"))]
+    #[ku(
+        name(b"AvdragsgillRanta"),
+        code("540"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub avdragsgill_ranta: Option<i32>,
-    #[ku(name(b"TotaltInbetaldRanta"), code("541"))]
+    #[ku(
+        name(b"TotaltInbetaldRanta"),
+        code("541"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub totalt_inbetald_ranta: Option<i32>,
-    #[ku(name(b"BetaldRantekompensation"), code("543"))]
+    #[ku(
+        name(b"BetaldRantekompensation"),
+        code("543"),
+        amount(true),
+        range(0, 999999999),
+        seq(6)
+    )]
     pub betald_rantekompensation: Option<i32>,
-    #[ku(name(b"GemensamtLan"), code("544"))]
+    #[ku(name(b"GemensamtLan"), code("544"), seq(7))]
     pub gemensamt_lan: Option<bool>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(8))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU25"), required(true), inner_ty(true))]
-    pub inkomsttagare: InkomsttagareKU25<'a>,
-    #[ku(name(b"UppgiftslamnareKU25"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU25"), required(true), inner_ty(true), seq(9))]
     pub uppgiftslamnare: UppgiftslamnareKU25<'a>,
+    #[ku(name(b"InkomsttagareKU25"), required(true), inner_ty(true), seq(10))]
+    pub inkomsttagare: InkomsttagareKU25<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU25"))]
 pub struct InkomsttagareKU25<'a> {
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(1))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(2))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(3))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(4))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(5))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(6))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(7))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(8))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(9))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(10))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(11))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(12))]
     pub fri_adress: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU25"))]
 pub struct UppgiftslamnareKU25<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -83,9 +109,9 @@ mod tests {
         )
         .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -142,7 +168,7 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku25).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku25, re_parsed);
     }
 }
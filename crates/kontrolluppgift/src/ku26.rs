@@ -1,61 +1,241 @@
-use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::{error, IdentitetsbeteckningForPerson, Landskod, Readable, Writable};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::borrow::Cow;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU26"))]
 pub struct KU26Type<'a> {
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(name(b"UppgiftslamnareKU26"), required(true), inner_ty(true), seq(1))]
+    pub uppgiftslamnare: UppgiftslamnareKU26<'a>,
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(2)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(3))]
     pub borttag: Option<bool>,
-    #[ku(name(b"BetaldTomtrattsavgald"), code("560"))]
+    #[ku(
+        name(b"BetaldTomtrattsavgald"),
+        code("560"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub betald_tomttsavgald: Option<i32>,
-    #[ku(name(b"Fastighetsbeteckning"), code("561"))]
-    pub fastighetsbeteckning: Option<Cow<'a, str>>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Fastighetsbeteckning"), code("561"), seq(5))]
+    pub fastighetsbeteckning: Option<Fastighetsbeteckning<'a>>,
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(6))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU26"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU26"), required(true), inner_ty(true), seq(7))]
     pub inkomsttagare: InkomsttagareKU26<'a>,
-    #[ku(name(b"UppgiftslamnareKU26"), required(true), inner_ty(true))]
-    pub uppgiftslamnare: UppgiftslamnareKU26<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+/// Fastighetsbeteckning (561), the property the tomträttsavgäld is paid for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fastighetsbeteckning<'a> {
+    /// A designation following the "Område Block:Enhet" structure
+    Fastighet(Fastighet),
+    /// A designation that does not follow the structure, kept as written
+    Ovrig(Cow<'a, str>),
+}
+
+impl Fastighetsbeteckning<'_> {
+    /// The longest designation the specification allows
+    pub const MAX_LENGTH: usize = 60;
+}
+
+impl Display for Fastighetsbeteckning<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Fastighetsbeteckning::Fastighet(fastighet) => write!(f, "{}", fastighet),
+            Fastighetsbeteckning::Ovrig(beteckning) => f.write_str(beteckning),
+        }
+    }
+}
+
+/// Parses a designation, falling back to [`Fastighetsbeteckning::Ovrig`] when it is not structured.
+/// Only empty or too long designations are rejected.
+impl TryFrom<&str> for Fastighetsbeteckning<'_> {
+    type Error = FastighetsbeteckningError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.parse::<Fastighet>() {
+            Ok(fastighet) => Ok(Fastighetsbeteckning::Fastighet(fastighet)),
+            Err(FastighetsbeteckningError::NotStructured(_)) => {
+                let value = value.trim();
+                let length = value.chars().count();
+                if length > Fastighetsbeteckning::MAX_LENGTH {
+                    return Err(FastighetsbeteckningError::TooLong(length));
+                }
+                Ok(Fastighetsbeteckning::Ovrig(Cow::Owned(value.to_string())))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'a, 'b: 'a> Readable<'a, 'b> for Fastighetsbeteckning<'a> {
+    fn get_str(data: Cow<'b, str>) -> Result<Self, error::Error> {
+        data.as_ref()
+            .try_into()
+            .map_err(|e: FastighetsbeteckningError| error::Error::UnexpectedToken(e.to_string()))
+    }
+}
+
+impl Writable for Fastighetsbeteckning<'_> {
+    fn get_str(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+/// A structured fastighetsbeteckning, e.g. "Stockholm Norrmalm 1:23"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fastighet {
+    /// Registerområde (usually the municipality) and trakt, e.g. "Stockholm Norrmalm"
+    pub omrade: String,
+    /// The block number, missing for designations written as "Område Enhet"
+    pub block: Option<u32>,
+    /// The unit number
+    pub enhet: u32,
+}
+
+impl Display for Fastighet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.block {
+            Some(block) => write!(f, "{} {}:{}", self.omrade, block, self.enhet),
+            None => write!(f, "{} {}", self.omrade, self.enhet),
+        }
+    }
+}
+
+/// Parses a designation, collapsing whitespace and capitalizing each word of the område
+impl FromStr for Fastighet {
+    type Err = FastighetsbeteckningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        static FASTIGHET_REGEX: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"^(?P<omrade>\D+?) (?:(?P<block>\d+) ?: ?)?(?P<enhet>\d+)$")
+                .expect("These are constructed and should be valid")
+        });
+
+        let collapsed = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        if collapsed.is_empty() {
+            return Err(FastighetsbeteckningError::Empty);
+        }
+        let captures = FASTIGHET_REGEX
+            .captures(&collapsed)
+            .ok_or_else(|| FastighetsbeteckningError::NotStructured(collapsed.clone()))?;
+        let number = |name: &str| -> Result<Option<u32>, FastighetsbeteckningError> {
+            captures
+                .name(name)
+                .map(|m| m.as_str().parse())
+                .transpose()
+                .map_err(|_| FastighetsbeteckningError::NotStructured(collapsed.clone()))
+        };
+        let fastighet = Fastighet {
+            omrade: captures["omrade"]
+                .split(' ')
+                .map(capitalize_word)
+                .collect::<Vec<_>>()
+                .join(" "),
+            block: number("block")?,
+            enhet: number("enhet")?.expect("enhet is not optional in the pattern"),
+        };
+        let length = fastighet.to_string().chars().count();
+        if length > Fastighetsbeteckning::MAX_LENGTH {
+            return Err(FastighetsbeteckningError::TooLong(length));
+        }
+        Ok(fastighet)
+    }
+}
+
+fn capitalize_word(word: &str) -> String {
+    word.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastighetsbeteckningError {
+    Empty,
+    TooLong(usize),
+    NotStructured(String),
+}
+
+impl Display for FastighetsbeteckningError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FastighetsbeteckningError::Empty => f.write_str("fastighetsbeteckning is empty"),
+            FastighetsbeteckningError::TooLong(length) => write!(
+                f,
+                "fastighetsbeteckning is {} characters, at most {} are allowed",
+                length,
+                Fastighetsbeteckning::MAX_LENGTH
+            ),
+            FastighetsbeteckningError::NotStructured(s) => write!(
+                f,
+                "{} is not a fastighetsbeteckning in the form \"Område Block:Enhet\"",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU26"))]
 pub struct InkomsttagareKU26<'a> {
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(1))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(2))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(3))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(4))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(5))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(6))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(7))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(8))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(9))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(10))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(11))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(12))]
     pub fri_adress: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU26"))]
 pub struct UppgiftslamnareKU26<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -76,9 +256,9 @@ mod tests {
         )
         .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -108,7 +288,7 @@ mod tests {
                     inkomstar: "2022".into(),
                     borttag: Some(true),
                     betald_tomttsavgald: Some(1),
-                    fastighetsbeteckning: Some("fastighetsbeteckning".into()),
+                    fastighetsbeteckning: Some("Stockholm Norrmalm 1:23".try_into().unwrap()),
                     specifikationsnummer: 5,
                     inkomsttagare: InkomsttagareKU26 {
                         inkomsttagare: Some("191612299279".try_into().unwrap()),
@@ -132,7 +312,40 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku26).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku26, re_parsed);
     }
+
+    #[test]
+    fn fastighetsbeteckning_is_normalized() {
+        let fastighet: Fastighet = "  stockholm   NORRMALM 1 : 23 ".parse().unwrap();
+        assert_eq!(
+            fastighet,
+            Fastighet {
+                omrade: "Stockholm Norrmalm".into(),
+                block: Some(1),
+                enhet: 23,
+            }
+        );
+        assert_eq!(fastighet.to_string(), "Stockholm Norrmalm 1:23");
+        assert_eq!(
+            "upplands-bro kungsängen 7"
+                .parse::<Fastighet>()
+                .unwrap()
+                .to_string(),
+            "Upplands-Bro Kungsängen 7"
+        );
+        assert!(matches!(
+            "Norrmalm".parse::<Fastighet>(),
+            Err(FastighetsbeteckningError::NotStructured(_))
+        ));
+        assert!(matches!(
+            format!("{} 1:1", "Lång".repeat(20)).parse::<Fastighet>(),
+            Err(FastighetsbeteckningError::TooLong(_))
+        ));
+        assert_eq!(
+            Fastighetsbeteckning::try_from("Hus3678").unwrap(),
+            Fastighetsbeteckning::Ovrig("Hus3678".into())
+        );
+    }
 }
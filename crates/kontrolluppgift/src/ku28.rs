@@ -1,84 +1,204 @@
-use crate::{IdentitetsbeteckningForPerson, KUDate, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{Validate, Violation};
+use crate::{error, IdentitetsbeteckningForPerson, KUDate, KUYear, Landskod, Readable, Writable};
+use kontrolluppgift_macros::{KUBuilder, KUStringEnum, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 /// Kontrolluppgift 28
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU28"))]
 pub struct KU28Type<'a> {
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(1))]
     pub delagare: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(2)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(3))]
     pub borttag: Option<bool>,
-    #[ku(name(b"UnderlagForInvesteraravdrag"), code("528"))]
+    #[ku(
+        name(b"UnderlagForInvesteraravdrag"),
+        code("528"),
+        amount(true),
+        range(0, 999999999),
+        seq(4)
+    )]
     pub underlag_for_investeraravdrag: Option<i32>,
-    #[ku(name(b"TotUnderlagInvesteraravdrag"), code("529"))]
+    #[ku(
+        name(b"TotUnderlagInvesteraravdrag"),
+        code("529"),
+        amount(true),
+        range(0, 999999999),
+        seq(5)
+    )]
     pub tot_underlag_investeraravdrag: Option<i32>,
-    #[ku(name(b"Betalningsar"), code("530"))]
-    pub betalningsar: Option<Cow<'a, str>>,
-    #[ku(name(b"AterforingAvyttring"), code("531"))]
+    #[ku(name(b"Betalningsar"), code("530"), seq(6))]
+    pub betalningsar: Option<KUYear>,
+    #[ku(name(b"AterforingAvyttring"), code("531"), seq(7))]
     pub aterforing_avyttring: Option<bool>,
-    #[ku(name(b"AterforingUtflyttning"), code("532"))]
+    #[ku(name(b"AterforingUtflyttning"), code("532"), seq(8))]
     pub aterforing_utflyttning: Option<bool>,
-    #[ku(name(b"AterforingHogVardeoverforing"), code("533"))]
+    #[ku(name(b"AterforingHogVardeoverforing"), code("533"), seq(9))]
     pub aterforing_hog_vardeoverforing: Option<bool>,
-    #[ku(name(b"AterforingInternaForvarv"), code("534"))]
+    #[ku(name(b"AterforingInternaForvarv"), code("534"), seq(10))]
     pub aterforing_interna_forvarv: Option<bool>,
-    #[ku(name(b"DatumForvarv"), code("535"))]
+    #[ku(name(b"DatumForvarv"), code("535"), seq(11))]
     pub datum_forvarv: Option<KUDate>,
-    #[ku(name(b"Region"), code("536"))]
-    pub region: Option<Cow<'a, str>>,
-    #[ku(name(b"Verksamhetsomrade"), code("537"))]
-    pub verksamhetsomrade: Option<Cow<'a, str>>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Region"), code("536"), seq(12))]
+    pub region: Option<Region>,
+    #[ku(name(b"Verksamhetsomrade"), code("537"), seq(13))]
+    pub verksamhetsomrade: Option<Verksamhetsomrade<'a>>,
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(14))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"InkomsttagareKU28"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU28"), required(true), inner_ty(true), seq(15))]
     pub inkomsttagare: InkomsttagareKU28<'a>,
-    #[ku(name(b"UppgiftslamnareKU28"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU28"), required(true), inner_ty(true), seq(16))]
     pub uppgiftslamnare: UppgiftslamnareKU28<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU28Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        let inkomstar: KUYear = match self.inkomstar.parse() {
+            Ok(year) => year,
+            Err(e) => {
+                violations.push(Violation::new(&["203"], e.to_string()));
+                return violations;
+            }
+        };
+        if let Some(datum_forvarv) = &self.datum_forvarv {
+            if datum_forvarv.year() != inkomstar {
+                violations.push(Violation::new(
+                    &["535", "203"],
+                    format!(
+                        "DatumForvarv {} is not in Inkomstar {}",
+                        datum_forvarv, inkomstar
+                    ),
+                ));
+            }
+        }
+        if let Some(betalningsar) = self.betalningsar {
+            if betalningsar > inkomstar || betalningsar.value() < inkomstar.value() - 1 {
+                violations.push(Violation::new(
+                    &["530", "203"],
+                    format!(
+                        "Betalningsar {} must be Inkomstar {} or the year before",
+                        betalningsar, inkomstar
+                    ),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+/// Region (536), the NUTS 2 region the company receiving the investment is active in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, KUStringEnum)]
+pub enum Region {
+    /// Stockholm
+    SE11,
+    /// Östra Mellansverige
+    SE12,
+    /// Småland med öarna
+    SE21,
+    /// Sydsverige
+    SE22,
+    /// Västsverige
+    SE23,
+    /// Norra Mellansverige
+    SE31,
+    /// Mellersta Norrland
+    SE32,
+    /// Övre Norrland
+    SE33,
+}
+
+/// Verksamhetsomrade (537), the SNI 2007 (NACE Rev. 2) code of the company receiving the investment.
+/// Given as the two digit division, optionally followed by the group, class and subclass digits,
+/// e.g. "62010"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verksamhetsomrade<'a>(Cow<'a, str>);
+
+impl Verksamhetsomrade<'_> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The two digit division (huvudgrupp) of the code
+    pub fn huvudgrupp(&self) -> &str {
+        &self.0[..2]
+    }
+}
+
+impl<'a, 'b: 'a> Readable<'a, 'b> for Verksamhetsomrade<'a> {
+    fn get_str(data: Cow<'b, str>) -> Result<Self, error::Error> {
+        data.as_ref()
+            .try_into()
+            .map_err(|e: &str| error::Error::UnexpectedToken(e.to_string()))
+    }
+}
+
+impl TryFrom<&str> for Verksamhetsomrade<'_> {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if (2..=5).contains(&value.len()) && value.bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(Verksamhetsomrade(Cow::Owned(value.to_string())));
+        }
+        Err("Not valid Verksamhetsomrade, expected two to five digits")
+    }
+}
+
+impl Writable for Verksamhetsomrade<'_> {
+    fn get_str(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU28"))]
 pub struct InkomsttagareKU28<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(2))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(3))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(4))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(5))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(6))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(7))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(8))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(9))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(10))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(11))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(12))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(13))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(14))]
     pub tin: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU28"))]
 pub struct UppgiftslamnareKU28<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -98,9 +218,9 @@ mod tests {
             fs::read_to_string("./EXEMPELFIL KONTROLLUPPGIFT INVESTERARAVDRAG (KU28)_2022.xml")
                 .unwrap();
 
-        let parsed = from_str(&*xml).unwrap();
+        let parsed = from_str(&xml).unwrap();
         let unparsed = to_string(&parsed).unwrap();
-        let parsed2 = from_str(&*unparsed).unwrap();
+        let parsed2 = from_str(&unparsed).unwrap();
         assert_eq!(parsed, parsed2);
     }
 
@@ -132,14 +252,14 @@ mod tests {
                     borttag: Some(true),
                     underlag_for_investeraravdrag: Some(1),
                     tot_underlag_investeraravdrag: Some(2),
-                    betalningsar: Some("2023".into()),
+                    betalningsar: Some("2023".parse().unwrap()),
                     aterforing_avyttring: Some(true),
                     aterforing_utflyttning: Some(false),
                     aterforing_hog_vardeoverforing: Some(true),
                     aterforing_interna_forvarv: Some(false),
                     datum_forvarv: Some("20210101".parse().unwrap()),
-                    region: Some("region".into()),
-                    verksamhetsomrade: Some("verksamhetsomrade".into()),
+                    region: Some(Region::SE12),
+                    verksamhetsomrade: Some("014".try_into().unwrap()),
                     specifikationsnummer: 5,
                     inkomsttagare: InkomsttagareKU28 {
                         landskod_tin: Some(Landskod::AF),
@@ -165,7 +285,41 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku28).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku28, re_parsed);
     }
+
+    #[test]
+    fn ku28_years_must_agree_with_inkomstar() {
+        let xml =
+            fs::read_to_string("./EXEMPELFIL KONTROLLUPPGIFT INVESTERARAVDRAG (KU28)_2022.xml")
+                .unwrap();
+        let mut parsed = from_str(&xml).unwrap();
+        let KU28(ku28) = &mut parsed.blanketter[0].blankettinnehall else {
+            panic!("expected a KU28")
+        };
+        assert!(ku28.validate().is_empty());
+
+        ku28.betalningsar = Some("2021".parse().unwrap());
+        ku28.datum_forvarv = Some("20220101".parse().unwrap());
+        assert!(ku28.validate().is_empty());
+
+        ku28.betalningsar = Some("2023".parse().unwrap());
+        ku28.datum_forvarv = Some("20211231".parse().unwrap());
+        let violations = ku28.validate();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].faltkoder, vec!["535", "203"]);
+        assert_eq!(violations[1].faltkoder, vec!["530", "203"]);
+    }
+
+    #[test]
+    fn verksamhetsomrade_is_an_sni_code() {
+        let kod = Verksamhetsomrade::try_from("62010").unwrap();
+        assert_eq!(kod.as_str(), "62010");
+        assert_eq!(kod.huvudgrupp(), "62");
+        assert!(Verksamhetsomrade::try_from("62").is_ok());
+        assert!(Verksamhetsomrade::try_from("6").is_err());
+        assert!(Verksamhetsomrade::try_from("620100").is_err());
+        assert!(Verksamhetsomrade::try_from("62A10").is_err());
+    }
 }
@@ -1,103 +1,156 @@
-use crate::{IdentitetsbeteckningForPerson, KUDate, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{vardepapper_rules, Validate, Violation};
+use crate::{IdentitetsbeteckningForPerson, Isin, KUDate, Landskod};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 /// Kontrolluppgift 31
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU31"))]
 pub struct KU31Type<'a> {
-    #[ku(name(b"AvdragenSkatt"), code("001"))]
+    #[ku(
+        name(b"AvdragenSkatt"),
+        code("001"),
+        amount(true),
+        range(0, 999999999),
+        seq(1)
+    )]
     pub avdragen_skatt: Option<i32>,
-    #[ku(name(b"AvdragenUtlandskSkatt"), code("002"))]
+    #[ku(
+        name(b"AvdragenUtlandskSkatt"),
+        code("002"),
+        amount(true),
+        range(0, 999999999),
+        seq(2)
+    )]
     pub avdragen_utlandsk_skatt: Option<i32>,
-    #[ku(name(b"AvdragenKupongskatt"), code("003"))]
+    #[ku(
+        name(b"AvdragenKupongskatt"),
+        code("003"),
+        amount(true),
+        range(0, 999999999),
+        seq(3)
+    )]
     pub avdragen_kupongskatt: Option<i32>,
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(4))]
     pub delagare: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(5)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(6))]
     pub borttag: Option<bool>,
-    #[ku(name(b"AnnanInkomst"), code("504"))]
+    #[ku(
+        name(b"AnnanInkomst"),
+        code("504"),
+        amount(true),
+        range(0, 999999999),
+        seq(7)
+    )]
     pub annan_inkomst: Option<i32>,
-    #[ku(name(b"Depanummer"), code("523"))]
+    #[ku(name(b"Depanummer"), code("523"), seq(8))]
     pub depanummer: Option<i32>,
-    #[ku(name(b"AndelAvDepan"), code("524"))]
+    #[ku(name(b"AndelAvDepan"), code("524"), seq(9))]
     pub andel_av_depan: Option<f32>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(10))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"VPNamn"), code("571"))]
+    #[ku(name(b"VPNamn"), code("571"), seq(11))]
     pub vp_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"ISIN"), code("572"))]
-    pub isin: Option<Cow<'a, str>>,
-    #[ku(name(b"UtbetaldUtdelning"), code("574"))]
+    #[ku(name(b"ISIN"), code("572"), seq(12))]
+    pub isin: Option<Isin<'a>>,
+    #[ku(
+        name(b"UtbetaldUtdelning"),
+        code("574"),
+        amount(true),
+        range(0, 999999999),
+        seq(13)
+    )]
     pub utbetald_utdelning: Option<i32>,
-    #[ku(name(b"AnnanKupongErsattning"), code("581"))]
+    #[ku(
+        name(b"AnnanKupongErsattning"),
+        code("581"),
+        amount(true),
+        range(0, 999999999),
+        seq(14)
+    )]
     pub annan_kupong_ersattning: Option<i32>,
-    #[ku(name(b"OkandVarde"), code("599"))]
+    #[ku(name(b"OkandVarde"), code("599"), seq(15))]
     pub okand_varde: Option<bool>,
-    #[ku(name(b"Avstamningsdag"), code("853"))]
+    #[ku(name(b"Avstamningsdag"), code("853"), seq(16))]
     pub avstamningsdag: Option<KUDate>,
-    #[ku(name(b"InkomsttagareKU31"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU31"), required(true), inner_ty(true), seq(17))]
     pub inkomsttagare: InkomsttagareKU31<'a>,
-    #[ku(name(b"UppgiftslamnareKU31"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU31"), required(true), inner_ty(true), seq(18))]
     pub uppgiftslamnare: UppgiftslamnareKU31<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU31Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        vardepapper_rules(&mut violations, self);
+        violations
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU31"))]
 pub struct InkomsttagareKU31<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Fodelseort"), code("077"))]
+    #[ku(name(b"Fodelseort"), code("077"), seq(2))]
     pub fodelseort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodFodelseort"), code("077"))]
+    #[ku(name(b"LandskodFodelseort"), code("078"), seq(3))]
     pub landskod_fodelseort: Option<Landskod>,
-    #[ku(name(b"LandskodHemvist"), code("079"))]
+    #[ku(name(b"LandskodHemvist"), code("079"), seq(4))]
     pub landskod_hemvist: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(5))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(6))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(7))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(8))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(9))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(10))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(11))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(12))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(13))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(14))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(15))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(16))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(17))]
     pub tin: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU31"))]
 pub struct UppgiftslamnareKU31<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::KontrolluppgiftType::{KU31};
+    use crate::KontrolluppgiftType::KU31;
     use crate::{
-        from_str, to_string, Arendeinformation, Avsandare, Blankett, Blankettgemensamt,
+        from_str, to_string, Arendeinformation, Avsandare, Blankett, Blankettgemensamt, IsinError,
         Kontaktperson, Kontrolluppgift, Landskod, TekniskKontaktperson, Uppgiftslamnare,
     };
 
@@ -135,7 +188,7 @@ mod tests {
                     andel_av_depan: Some(0.2),
                     specifikationsnummer: 5,
                     vp_namn: Some("test".into()),
-                    isin: Some("isin".into()),
+                    isin: Some("SE0005624905".try_into().unwrap()),
                     utbetald_utdelning: Some(6),
                     annan_kupong_ersattning: Some(7),
                     okand_varde: Some(false),
@@ -167,7 +220,34 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku31).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        assert!(unparsed.contains("<LandskodFodelseort faltkod=\"078\">AE</LandskodFodelseort>"));
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku31, re_parsed);
     }
+
+    #[test]
+    fn isin_check_digit_is_validated() {
+        let isin = Isin::try_from("SE0005624905").unwrap();
+        assert_eq!(isin.landskod(), Some(Landskod::SE));
+        assert_eq!(
+            Isin::try_from("US0378331005").unwrap().landskod(),
+            Some(Landskod::US)
+        );
+        assert_eq!(Isin::try_from("XS1234567896").unwrap().landskod(), None);
+        assert_eq!(
+            Isin::try_from("SE0005624904"),
+            Err(IsinError::InvalidCheckDigit {
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(
+            Isin::try_from("SE000562490"),
+            Err(IsinError::InvalidLength(11))
+        );
+        assert!(matches!(
+            Isin::try_from("se0005624905"),
+            Err(IsinError::InvalidFormat(_))
+        ));
+    }
 }
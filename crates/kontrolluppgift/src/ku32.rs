@@ -1,80 +1,104 @@
-use crate::{IdentitetsbeteckningForPerson, Landskod};
-use kontrolluppgift_macros::{KontrolluppgiftRead, KontrolluppgiftWrite};
+use crate::validation::{requires, vardepapper_rules, Validate, Violation};
+use crate::{IdentitetsbeteckningForPerson, Isin, Landskod};
+use kontrolluppgift_macros::{KUBuilder, KontrolluppgiftRead, KontrolluppgiftWrite};
 use std::borrow::Cow;
 
 /// Kontrolluppgift 32
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("KU32"))]
 pub struct KU32Type<'a> {
-    #[ku(name(b"Delagare"), code("061"))]
+    #[ku(name(b"Delagare"), code("061"), seq(1))]
     pub delagare: Option<bool>,
-    #[ku(name(b"Inkomstar"), code("203"), required(true))]
+    #[ku(
+        name(b"Inkomstar"),
+        code("203"),
+        required(true),
+        pattern("[0-9]{4}"),
+        seq(2)
+    )]
     pub inkomstar: Cow<'a, str>,
-    #[ku(name(b"Borttag"), code("205"))]
+    #[ku(name(b"Borttag"), code("205"), seq(3))]
     pub borttag: Option<bool>,
-    #[ku(name(b"Depanummer"), code("523"))]
+    #[ku(name(b"Depanummer"), code("523"), seq(4))]
     pub depanummer: Option<i32>,
-    #[ku(name(b"AndelAvDepan"), code("524"))]
+    #[ku(name(b"AndelAvDepan"), code("524"), seq(5))]
     pub andel_av_depan: Option<f32>,
-    #[ku(name(b"Specifikationsnummer"), code("570"), required(true))]
+    #[ku(name(b"Specifikationsnummer"), code("570"), required(true), seq(6))]
     pub specifikationsnummer: i32,
-    #[ku(name(b"VPNamn"), code("571"))]
+    #[ku(name(b"VPNamn"), code("571"), seq(7))]
     pub vp_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"ISIN"), code("572"))]
-    pub isin: Option<Cow<'a, str>>,
-    #[ku(name(b"AvyttradTillISK"), code("573"))]
+    #[ku(name(b"ISIN"), code("572"), seq(8))]
+    pub isin: Option<Isin<'a>>,
+    #[ku(name(b"AvyttradTillISK"), code("573"), seq(9))]
     pub avyttrad_till_isk: Option<bool>,
-    #[ku(name(b"AntalAvyttrade"), code("576"))]
+    #[ku(name(b"AntalAvyttrade"), code("576"), seq(10))]
     pub antal_avyttrade: Option<i32>,
-    #[ku(name(b"OkandVarde"), code("599"))]
+    #[ku(name(b"OkandVarde"), code("599"), seq(11))]
     pub okand_varde: Option<bool>,
-    #[ku(name(b"ErhallenErsattning"), code("810"))]
+    #[ku(
+        name(b"ErhallenErsattning"),
+        code("810"),
+        amount(true),
+        range(0, 999999999),
+        seq(12)
+    )]
     pub erhallen_ersattning: Option<i32>,
-    #[ku(name(b"InkomsttagareKU32"), required(true), inner_ty(true))]
+    #[ku(name(b"InkomsttagareKU32"), required(true), inner_ty(true), seq(13))]
     pub inkomsttagare: InkomsttagareKU32<'a>,
-    #[ku(name(b"UppgiftslamnareKU32"), required(true), inner_ty(true))]
+    #[ku(name(b"UppgiftslamnareKU32"), required(true), inner_ty(true), seq(14))]
     pub uppgiftslamnare: UppgiftslamnareKU32<'a>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+impl Validate for KU32Type<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        vardepapper_rules(&mut violations, self);
+        requires(&mut violations, self, "573", "576");
+        violations
+    }
+}
+
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("InkomsttagareKU32"))]
 pub struct InkomsttagareKU32<'a> {
-    #[ku(name(b"LandskodTIN"), code("076"))]
+    #[ku(name(b"LandskodTIN"), code("076"), seq(1))]
     pub landskod_tin: Option<Landskod>,
-    #[ku(name(b"Inkomsttagare"), code("215"))]
+    #[ku(name(b"Inkomsttagare"), code("215"), seq(2))]
     pub inkomsttagare: Option<IdentitetsbeteckningForPerson<'a>>,
-    #[ku(name(b"Fornamn"), code("216"))]
+    #[ku(name(b"Fornamn"), code("216"), seq(3))]
     pub fornamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Efternamn"), code("217"))]
+    #[ku(name(b"Efternamn"), code("217"), seq(4))]
     pub efternamn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress"), code("218"))]
+    #[ku(name(b"Gatuadress"), code("218"), seq(5))]
     pub gatuadress: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"), code("219"))]
+    #[ku(name(b"Postnummer"), code("219"), seq(6))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"), code("220"))]
+    #[ku(name(b"Postort"), code("220"), seq(7))]
     pub postort: Option<Cow<'a, str>>,
-    #[ku(name(b"LandskodPostort"), code("221"))]
+    #[ku(name(b"LandskodPostort"), code("221"), seq(8))]
     pub landskod_postort: Option<Landskod>,
-    #[ku(name(b"Fodelsetid"), code("222"))]
+    #[ku(name(b"Fodelsetid"), code("222"), pattern("[0-9]{8}"), seq(9))]
     pub fodelsetid: Option<Cow<'a, str>>,
-    #[ku(name(b"AnnatIDNr"), code("224"))]
+    #[ku(name(b"AnnatIDNr"), code("224"), seq(10))]
     pub annat_id_nr: Option<Cow<'a, str>>,
-    #[ku(name(b"OrgNamn"), code("226"))]
+    #[ku(name(b"OrgNamn"), code("226"), seq(11))]
     pub org_namn: Option<Cow<'a, str>>,
-    #[ku(name(b"Gatuadress2"), code("228"))]
+    #[ku(name(b"Gatuadress2"), code("228"), seq(12))]
     pub gatuadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"FriAdress"), code("230"))]
+    #[ku(name(b"FriAdress"), code("230"), seq(13))]
     pub fri_adress: Option<Cow<'a, str>>,
-    #[ku(name(b"TIN"), code("252"))]
+    #[ku(name(b"TIN"), code("252"), seq(14))]
     pub tin: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("UppgiftslamnareKU32"))]
 pub struct UppgiftslamnareKU32<'a> {
-    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true))]
+    #[ku(name(b"UppgiftslamnarId"), code("201"), required(true), seq(1))]
     pub uppgiftslamnar_id: Cow<'a, str>,
-    #[ku(name(b"NamnUppgiftslamnare"), code("202"))]
+    #[ku(name(b"NamnUppgiftslamnare"), code("202"), seq(2))]
     pub namn_uppgiftslamnare: Option<Cow<'a, str>>,
 }
 
@@ -117,7 +141,7 @@ mod tests {
                     andel_av_depan: Some(0.2),
                     specifikationsnummer: 5,
                     vp_namn: Some("test".into()),
-                    isin: Some("isin".into()),
+                    isin: Some("SE0000115446".try_into().unwrap()),
                     okand_varde: Some(false),
                     inkomsttagare: InkomsttagareKU32 {
                         landskod_tin: Some(Landskod::AF),
@@ -146,7 +170,43 @@ mod tests {
             }],
         };
         let unparsed = to_string(&ku32).unwrap();
-        let re_parsed = from_str(&*unparsed).unwrap();
+        let re_parsed = from_str(&unparsed).unwrap();
         assert_eq!(ku32, re_parsed);
     }
+
+    #[test]
+    fn ku32_avyttrad_till_isk_requires_antal() {
+        let ku32 = |antal_avyttrade| KU32Type {
+            delagare: None,
+            inkomstar: "2022".into(),
+            borttag: None,
+            depanummer: None,
+            andel_av_depan: None,
+            specifikationsnummer: 1,
+            vp_namn: Some("Aktie".into()),
+            isin: None,
+            avyttrad_till_isk: Some(true),
+            antal_avyttrade,
+            okand_varde: None,
+            erhallen_ersattning: Some(1000),
+            inkomsttagare: InkomsttagareKU32 {
+                inkomsttagare: Some("191612299279".try_into().unwrap()),
+                ..Default::default()
+            },
+            uppgiftslamnare: UppgiftslamnareKU32 {
+                uppgiftslamnar_id: "165599990602".into(),
+                namn_uppgiftslamnare: None,
+            },
+        };
+        assert!(ku32(Some(10)).validate().is_empty());
+        let violations: Vec<_> = ku32(None)
+            .validate()
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            violations,
+            vec!["[573, 576] AntalAvyttrade is required when AvyttradTillISK is present (see KU32 faltkod 573)"]
+        );
+    }
 }
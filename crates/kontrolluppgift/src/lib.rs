@@ -1,4 +1,10 @@
+pub mod builder;
+pub mod correction;
+pub mod diff;
 pub mod error;
+pub mod form;
+pub mod inkomsttagare;
+pub mod key;
 pub mod ku10;
 pub mod ku13;
 pub mod ku14;
@@ -14,6 +20,12 @@ pub mod ku28;
 pub mod ku30;
 pub mod ku31;
 pub mod ku32;
+pub mod merge;
+pub mod meta;
+pub mod redaction;
+pub mod split;
+pub mod validation;
+pub mod value;
 
 use crate::error::Error;
 use crate::error::Error::{MissingElement, NonDecodable};
@@ -34,7 +46,7 @@ use crate::ku31::KU31Type;
 use crate::ku32::KU32Type;
 use crate::KontrolluppgiftType::*;
 use kontrolluppgift_macros::{
-    KUStringEnum, KUVariantsEnum, KontrolluppgiftRead, KontrolluppgiftWrite,
+    KUBuilder, KUStringEnum, KUVariantsEnum, KontrolluppgiftRead, KontrolluppgiftWrite,
 };
 use once_cell::sync::Lazy;
 use quick_xml::events::{BytesStart, BytesText, Event};
@@ -80,19 +92,23 @@ impl<'a> Kontrolluppgift<'a> {
     }
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("Blankettgemensamt"))]
 pub struct Blankettgemensamt<'a> {
-    #[ku(name(b"Uppgiftslamnare"), inner_ty(true), required(true))]
+    #[ku(name(b"Uppgiftslamnare"), inner_ty(true), required(true), seq(1))]
     pub uppgiftslamnare: Uppgiftslamnare<'a>,
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("Uppgiftslamnare"))]
 pub struct Uppgiftslamnare<'a> {
-    #[ku(name(b"UppgiftslamnarePersOrgnr"), required(true))]
+    #[ku(name(b"UppgiftslamnarePersOrgnr"), required(true), seq(1))]
     pub uppgiftslamnare_pers_orgnr: Cow<'a, str>,
-    #[ku(name(b"Kontaktperson"), required(true), inner_ty(true))]
+    #[ku(name(b"Kontaktperson"), required(true), inner_ty(true), seq(2))]
     pub kontaktperson: Kontaktperson<'a>,
 }
 
@@ -139,66 +155,82 @@ pub enum KontrolluppgiftType<'a> {
     KU32(KU32Type<'a>),
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder)]
 #[ku(name("Arendeinformation"))]
 pub struct Arendeinformation<'a> {
-    #[ku(name(b"Arendeagare"), required(true))]
+    #[ku(name(b"Arendeagare"), required(true), seq(1))]
     pub arendeagare: Cow<'a, str>,
-    #[ku(name(b"Period"), required(true))]
+    #[ku(name(b"Period"), required(true), seq(2))]
     pub period: Cow<'a, str>,
-    #[ku(name(b"Arendenummer"))]
+    #[ku(name(b"Arendenummer"), seq(3))]
     pub arendenummer: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("Kontaktperson"))]
 pub struct Kontaktperson<'a> {
-    #[ku(name(b"Namn"), required(true))]
+    #[ku(name(b"Namn"), required(true), max_len(35), seq(1))]
     pub namn: Cow<'a, str>,
-    #[ku(name(b"Telefon"), required(true))]
+    #[ku(name(b"Telefon"), required(true), seq(2))]
     pub telefon: Cow<'a, str>,
-    #[ku(name(b"Epostadress"), required(true))]
+    #[ku(name(b"Epostadress"), required(true), seq(3))]
     pub epostadress: Cow<'a, str>,
-    #[ku(name(b"Sakomrade"))]
+    #[ku(name(b"Sakomrade"), seq(4))]
     pub sakomrade: Option<Cow<'a, str>>,
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("Avsandare"))]
 pub struct Avsandare<'a> {
-    #[ku(name(b"Programnamn"), required(true))]
+    #[ku(name(b"Programnamn"), required(true), seq(1))]
     pub programnamn: Cow<'a, str>,
-    #[ku(name(b"Organisationsnummer"), required(true))]
+    #[ku(name(b"Organisationsnummer"), required(true), seq(2))]
     pub organisationsnummer: Cow<'a, str>,
-    #[ku(name(b"TekniskKontaktperson"), required(true), inner_ty(true))]
+    #[ku(name(b"TekniskKontaktperson"), required(true), inner_ty(true), seq(3))]
     pub teknisk_kontaktperson: TekniskKontaktperson<'a>,
-    #[ku(name(b"Skapad"), required(true))]
+    #[ku(name(b"Skapad"), required(true), seq(4))]
     pub skapad: Cow<'a, str>,
 }
 
-#[derive(Debug, Default, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+#[derive(
+    Debug, Default, Clone, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite, KUBuilder,
+)]
 #[ku(name("TekniskKontaktperson"))]
 pub struct TekniskKontaktperson<'a> {
-    #[ku(name(b"Namn"), required(true))]
+    #[ku(name(b"Namn"), required(true), max_len(35), seq(1))]
     pub namn: Cow<'a, str>,
-    #[ku(name(b"Telefon"), required(true))]
+    #[ku(name(b"Telefon"), required(true), seq(2))]
     pub telefon: Cow<'a, str>,
-    #[ku(name(b"Epostadress"), required(true))]
+    #[ku(name(b"Epostadress"), required(true), seq(3))]
     pub epostadress: Cow<'a, str>,
-    #[ku(name(b"Utdelningsadress1"))]
+    #[ku(name(b"Utdelningsadress1"), seq(4))]
     pub utdelningsadress1: Option<Cow<'a, str>>,
-    #[ku(name(b"Utdelningsadress2"))]
+    #[ku(name(b"Utdelningsadress2"), seq(5))]
     pub utdelningsadress2: Option<Cow<'a, str>>,
-    #[ku(name(b"Postnummer"))]
+    #[ku(name(b"Postnummer"), seq(6))]
     pub postnummer: Option<Cow<'a, str>>,
-    #[ku(name(b"Postort"))]
+    #[ku(name(b"Postort"), seq(7))]
     pub postort: Option<Cow<'a, str>>,
 }
 
 /// Deserialize xml into rust types
 /// It also does not currently validate any namespace information.
-pub fn from_str(str: &str) -> Result<Kontrolluppgift, Error> {
+pub fn from_str(str: &str) -> Result<Kontrolluppgift<'_>, Error> {
+    read(str, false)
+}
+
+/// Like [from_str], but fails unless every element comes in the order of the xsd sequence
+pub fn from_str_strict(str: &str) -> Result<Kontrolluppgift<'_>, Error> {
+    read(str, true)
+}
+
+fn read(str: &str, strict: bool) -> Result<Kontrolluppgift<'_>, Error> {
     let mut reader = NsReader::from_str(str);
+    let mut position = None;
 
     let mut g_avsandare = None;
     let mut blankettgemensamt = None;
@@ -212,27 +244,33 @@ pub fn from_str(str: &str) -> Result<Kontrolluppgift, Error> {
                 b"Skatteverket" => {
                     //Noop just becuase of the way its structured
                 }
-                b"Avsandare" => g_avsandare = Some(Avsandare::read(&mut reader, &element)?),
+                b"Avsandare" => {
+                    check_sequence(strict, &mut position, 0, false, &element, "Skatteverket")?;
+                    g_avsandare = Some(Avsandare::read(&mut reader, &element, strict)?)
+                }
                 b"Blankettgemensamt" => {
-                    blankettgemensamt = Some(Blankettgemensamt::read(&mut reader, &element)?)
+                    check_sequence(strict, &mut position, 1, false, &element, "Skatteverket")?;
+                    blankettgemensamt =
+                        Some(Blankettgemensamt::read(&mut reader, &element, strict)?)
+                }
+                b"Blankett" => {
+                    check_sequence(strict, &mut position, 2, true, &element, "Skatteverket")?;
+                    blanketter.push(Blankett::read(&mut reader, &element, strict)?)
                 }
-                b"Blankett" => blanketter.push(Blankett::read(&mut reader, &element)?),
                 _ => unexpected_element(&element)?,
             },
-            Event::End(element) => {
-                if element.local_name().as_ref() == b"Skatteverket" {
-                    return Ok(Kontrolluppgift {
-                        avsandare: g_avsandare.ok_or_else(|| MissingElement {
-                            missing: "Avsandare".into(),
-                            reading: "Skatteverket".into(),
-                        })?,
-                        blankettgemensamt: blankettgemensamt.ok_or_else(|| MissingElement {
-                            missing: "Blankettgemensamt".into(),
-                            reading: "Skatteverket".into(),
-                        })?,
-                        blanketter,
-                    });
-                }
+            Event::End(element) if element.local_name().as_ref() == b"Skatteverket" => {
+                return Ok(Kontrolluppgift {
+                    avsandare: g_avsandare.ok_or_else(|| MissingElement {
+                        missing: "Avsandare".into(),
+                        reading: "Skatteverket".into(),
+                    })?,
+                    blankettgemensamt: blankettgemensamt.ok_or_else(|| MissingElement {
+                        missing: "Blankettgemensamt".into(),
+                        reading: "Skatteverket".into(),
+                    })?,
+                    blanketter,
+                });
             }
             Event::Eof => return Err(Error::UnexpectedEof("While reading Skatteverket".into())),
             _ => (),
@@ -251,8 +289,22 @@ pub fn to_string(kontrolluppgift: &Kontrolluppgift) -> Result<String, Error> {
     Ok(res)
 }
 
+/// Writes a Kontrolluppgift to the writer as it goes, without building the whole document in memory
+pub fn to_writer<W: std::io::Write>(
+    writer: W,
+    kontrolluppgift: &Kontrolluppgift,
+) -> Result<(), Error> {
+    kontrolluppgift.write(&mut Writer::new(writer))?;
+    Ok(())
+}
+
 impl<'a> Blankett<'a> {
-    fn read(reader: &mut NsReader<&'a [u8]>, tag: &BytesStart) -> Result<Self, Error> {
+    fn read(
+        reader: &mut NsReader<&'a [u8]>,
+        tag: &BytesStart,
+        strict: bool,
+    ) -> Result<Self, Error> {
+        let mut position = None;
         let mut nummer = None;
         let mut arendeinformation = None;
         let mut blankettinnehall = None;
@@ -274,30 +326,30 @@ impl<'a> Blankett<'a> {
             match reader.read_event()? {
                 Event::Start(element) => match element.local_name().as_ref() {
                     b"Arendeinformation" => {
-                        arendeinformation = Some(Arendeinformation::read(reader, &element)?)
+                        check_sequence(strict, &mut position, 0, false, &element, "Blankett")?;
+                        arendeinformation = Some(Arendeinformation::read(reader, &element, strict)?)
                     }
                     b"Blankettinnehall" => {
-                        blankettinnehall = KontrolluppgiftType::read(reader)?;
+                        check_sequence(strict, &mut position, 1, false, &element, "Blankett")?;
+                        blankettinnehall = KontrolluppgiftType::read(reader, strict)?;
                     }
                     &_ => unexpected_element(&element)?,
                 },
-                Event::End(element) => {
-                    if element.name() == tag.name() {
-                        return Ok(Self {
-                            nummer: nummer.ok_or_else(|| MissingElement {
-                                missing: "nummer".into(),
-                                reading: "Blankett".into(),
-                            })?,
-                            arendeinformation: arendeinformation.ok_or_else(|| MissingElement {
-                                missing: "Arendeinformation".into(),
-                                reading: "Blankett".into(),
-                            })?,
-                            blankettinnehall: blankettinnehall.ok_or_else(|| MissingElement {
-                                missing: "Blankettinnehall".into(),
-                                reading: "Blankett".into(),
-                            })?,
-                        });
-                    }
+                Event::End(element) if element.name() == tag.name() => {
+                    return Ok(Self {
+                        nummer: nummer.ok_or_else(|| MissingElement {
+                            missing: "nummer".into(),
+                            reading: "Blankett".into(),
+                        })?,
+                        arendeinformation: arendeinformation.ok_or_else(|| MissingElement {
+                            missing: "Arendeinformation".into(),
+                            reading: "Blankett".into(),
+                        })?,
+                        blankettinnehall: blankettinnehall.ok_or_else(|| MissingElement {
+                            missing: "Blankettinnehall".into(),
+                            reading: "Blankett".into(),
+                        })?,
+                    });
                 }
                 Event::Eof => return Err(Error::UnexpectedEof("While reading Blankett".into())),
                 _ => {}
@@ -319,7 +371,8 @@ pub enum DeError {
 }
 
 trait KontrolluppgiftRead<'a> {
-    fn read(reader: &mut NsReader<&'a [u8]>, tag: &BytesStart) -> Result<Self, Error>
+    /// In strict mode child elements must come in the order of the xsd sequence
+    fn read(reader: &mut NsReader<&'a [u8]>, tag: &BytesStart, strict: bool) -> Result<Self, Error>
     where
         Self: Sized;
 }
@@ -330,6 +383,46 @@ trait KontrolluppgiftWrite {
         W: std::io::Write;
 }
 
+/// Reads an element of an xs:choice, generated by the `KontrolluppgiftChoice` derive
+#[allow(dead_code)] // none of the implemented forms contain a choice yet
+trait KontrolluppgiftChoice<'a> {
+    /// Whether the element is one of the alternatives of the choice
+    fn is_choice(element: &[u8]) -> bool;
+    fn read_choice(
+        reader: &mut NsReader<&'a [u8]>,
+        element: BytesStart,
+        strict: bool,
+    ) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+/// In strict mode, fails when a child element comes after one that is later in the xsd sequence,
+/// a repeatable element may follow itself
+fn check_sequence(
+    strict: bool,
+    last: &mut Option<usize>,
+    position: usize,
+    repeatable: bool,
+    element: &BytesStart,
+    reading: &str,
+) -> Result<(), Error> {
+    let out_of_order = |last: &usize| match repeatable {
+        true => position < *last,
+        false => position <= *last,
+    };
+    if strict && last.as_ref().is_some_and(out_of_order) {
+        return Err(Error::OutOfSequence {
+            element: std::str::from_utf8(element.local_name().as_ref())
+                .map_err(|e| NonDecodable(Some(e)))?
+                .into(),
+            reading: reading.into(),
+        });
+    }
+    *last = Some(position);
+    Ok(())
+}
+
 fn unexpected_element<E>(element: &BytesStart) -> Result<E, Error> {
     Err(Error::UnexpectedToken(
         std::str::from_utf8(element.name().as_ref())
@@ -494,6 +587,15 @@ where
     }
 }
 
+impl<T> Writable for Option<&T>
+where
+    T: Writable,
+{
+    fn get_str(&self) -> Option<String> {
+        self.and_then(Writable::get_str)
+    }
+}
+
 impl<'a> Writable for Cow<'a, str> {
     fn get_str(&self) -> Option<String> {
         Some(self.to_string())
@@ -538,6 +640,20 @@ impl Writable for KUDate {
     }
 }
 
+impl<'a, 'b> Readable<'a, 'b> for KUYear {
+    fn get_str(data: Cow<str>) -> Result<Self, Error> {
+        data.as_ref()
+            .parse()
+            .map_err(|e| Error::UnexpectedToken(format!("expected year got: {}", &e)))
+    }
+}
+
+impl Writable for KUYear {
+    fn get_str(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
 // based on regex: ((19|20)[0-9][0-9](((01|03|05|07|08|10|12)(0[1-9]|1[0-9]|2[0-9]|3[0-1]))|((04|06|09|11)(0[1-9]|1[0-9]|2[0-9]|30))|((02)(0[1-9]|1[0-9]|2[0-8]))))|((19|20)(04|08|12|16|20|24|28|32|36|40|44|48|52|56|60|64|68|72|76|80|84|88|92|96)(0229))|(20000229)
 #[derive(Debug, Clone, PartialEq)]
 pub struct KUDate(Date);
@@ -546,7 +662,7 @@ impl Display for KUDate {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let format =
             format_description::parse("[year][month][day]").expect("this pattern should be valid");
-        f.write_str(&*self.0.format(&format).unwrap())
+        f.write_str(&self.0.format(&format).unwrap())
     }
 }
 
@@ -556,8 +672,8 @@ impl FromStr for KUDate {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let format = format_description::parse("[year][month][day]")
             .expect("this format is supposed to be valid");
-        let date = Date::parse(s, &format).map_err(|p| KUDateError::CouldNotBeParsed(p))?;
-        KUDate::from_date(date).map_err(|e| KUDateError::YearOutOfRange(e))
+        let date = Date::parse(s, &format).map_err(KUDateError::CouldNotBeParsed)?;
+        KUDate::from_date(date).map_err(KUDateError::YearOutOfRange)
     }
 }
 
@@ -569,11 +685,15 @@ pub enum KUDateError {
 
 impl KUDate {
     pub fn from_date(date: Date) -> Result<Self, YearOutOfRangeError> {
-        return if let 1900..=2099 = date.year() {
+        if let 1900..=2099 = date.year() {
             Ok(KUDate(date))
         } else {
             Err(YearOutOfRangeError(date.year()))
-        };
+        }
+    }
+
+    pub fn year(&self) -> KUYear {
+        KUYear(self.0.year())
     }
 }
 #[derive(Debug, Clone)]
@@ -594,13 +714,98 @@ impl Display for KUDateError {
     }
 }
 
+/// A year in the range a kontrolluppgift can represent, written as four digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KUYear(i32);
+
+impl KUYear {
+    pub fn new(year: i32) -> Result<Self, YearOutOfRangeError> {
+        if let 1900..=2099 = year {
+            Ok(KUYear(year))
+        } else {
+            Err(YearOutOfRangeError(year))
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.0
+    }
+}
+
+impl Display for KUYear {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.0)
+    }
+}
+
+impl FromStr for KUYear {
+    type Err = KUYearError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 4 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(KUYearError::CouldNotBeParsed(s.to_string()));
+        }
+        let year = s
+            .parse()
+            .map_err(|_| KUYearError::CouldNotBeParsed(s.to_string()))?;
+        KUYear::new(year).map_err(KUYearError::YearOutOfRange)
+    }
+}
+
+#[derive(Debug)]
+pub enum KUYearError {
+    CouldNotBeParsed(String),
+    YearOutOfRange(YearOutOfRangeError),
+}
+
+impl Display for KUYearError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            KUYearError::CouldNotBeParsed(s) => write!(f, "{} is not a four digit year", s),
+            KUYearError::YearOutOfRange(e) => write!(f, "{}", e),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, KUStringEnum)]
 pub enum NarfartFjarrfart {
     N,
     F,
 }
 
-#[derive(Debug, PartialEq, KUStringEnum)]
+/// Ersattningskod (004), the kind of payment reported in ErsattningBelopp (005) on KU18 and KU19
+#[derive(Debug, Clone, Copy, PartialEq, Eq, KUStringEnum)]
+pub enum Ersattningskod {
+    /// Pension from a private pensionsförsäkring
+    #[ku(name("401"))]
+    PrivatPension,
+    /// Tjänstepension
+    #[ku(name("402"))]
+    Tjanstepension,
+    /// Livränta that is not paid as a pension
+    #[ku(name("403"))]
+    Livranta,
+    /// Sjukpension, sjukersättning or aktivitetsersättning paid from an insurance
+    #[ku(name("404"))]
+    Sjukpension,
+    /// Compensation from an avtalsgruppsjukförsäkring
+    #[ku(name("405"))]
+    Avtalsgruppsjukforsakring,
+    /// Compensation from a sjuk- or olycksfallsförsäkring
+    #[ku(name("406"))]
+    SjukOlycksfallsforsakring,
+    /// Payment from an individuellt pensionssparande (IPS)
+    #[ku(name("407"))]
+    Pensionssparande,
+    /// Återköp of a pensionsförsäkring
+    #[ku(name("408"))]
+    Aterkop,
+    /// Other taxable compensation
+    #[ku(name("409"))]
+    OvrigErsattning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, KUStringEnum)]
 pub enum Landskod {
     AD,
     AE,
@@ -855,7 +1060,322 @@ pub enum Landskod {
     ZW,
 }
 
-#[derive(Debug, PartialEq)]
+impl Landskod {
+    /// Iterates over every landskod
+    pub fn iter() -> impl Iterator<Item = &'static Landskod> {
+        Landskod::VARIANTS.iter()
+    }
+
+    /// The ISO 3166-1 alpha-3 code, e.g. "SWE"
+    pub fn alpha3(&self) -> &'static str {
+        self.metadata().0
+    }
+
+    /// The ISO 3166-1 numeric code, `None` for XK (Kosovo) which has no assigned code
+    pub fn numerisk_kod(&self) -> Option<u16> {
+        self.metadata().1
+    }
+
+    /// The country name in Swedish, e.g. "Sverige"
+    pub fn svenskt_namn(&self) -> &'static str {
+        self.metadata().2
+    }
+
+    /// The country name in English, e.g. "Sweden"
+    pub fn engelskt_namn(&self) -> &'static str {
+        self.metadata().3
+    }
+
+    pub fn from_alpha3(alpha3: &str) -> Option<Landskod> {
+        Landskod::iter().find(|l| l.alpha3() == alpha3).copied()
+    }
+
+    pub fn from_numerisk_kod(kod: u16) -> Option<Landskod> {
+        Landskod::iter()
+            .find(|l| l.numerisk_kod() == Some(kod))
+            .copied()
+    }
+
+    /// Member state of the European Union
+    pub fn is_eu(&self) -> bool {
+        use Landskod::*;
+        const EU: &[Landskod] = &[
+            AT, BE, BG, CY, CZ, DE, DK, EE, ES, FI, FR, GR, HR, HU, IE, IT, LT, LU, LV, MT, NL, PL,
+            PT, RO, SE, SI, SK,
+        ];
+        EU.contains(self)
+    }
+
+    /// Part of the European Economic Area, the EU member states together with Iceland, Liechtenstein and Norway
+    pub fn is_ees(&self) -> bool {
+        self.is_eu() || matches!(self, Landskod::IS | Landskod::LI | Landskod::NO)
+    }
+
+    /// The Nordic countries together with the autonomous Åland, Faroe Islands and Greenland
+    pub fn is_nordisk(&self) -> bool {
+        use Landskod::*;
+        matches!(self, SE | DK | FI | NO | IS | AX | FO | GL)
+    }
+
+    #[rustfmt::skip]
+    fn metadata(&self) -> (&'static str, Option<u16>, &'static str, &'static str) {
+        match self {
+            Landskod::AD => ("AND", Some(20), "Andorra", "Andorra"),
+            Landskod::AE => ("ARE", Some(784), "Förenade arabemiraten", "United Arab Emirates"),
+            Landskod::AF => ("AFG", Some(4), "Afghanistan", "Afghanistan"),
+            Landskod::AG => ("ATG", Some(28), "Antigua och Barbuda", "Antigua and Barbuda"),
+            Landskod::AI => ("AIA", Some(660), "Anguilla", "Anguilla"),
+            Landskod::AL => ("ALB", Some(8), "Albanien", "Albania"),
+            Landskod::AN => ("ANT", Some(530), "Nederländska Antillerna", "Netherlands Antilles"),
+            Landskod::AM => ("ARM", Some(51), "Armenien", "Armenia"),
+            Landskod::AO => ("AGO", Some(24), "Angola", "Angola"),
+            Landskod::AQ => ("ATA", Some(10), "Antarktis", "Antarctica"),
+            Landskod::AR => ("ARG", Some(32), "Argentina", "Argentina"),
+            Landskod::AS => ("ASM", Some(16), "Amerikanska Samoa", "American Samoa"),
+            Landskod::AT => ("AUT", Some(40), "Österrike", "Austria"),
+            Landskod::AU => ("AUS", Some(36), "Australien", "Australia"),
+            Landskod::AW => ("ABW", Some(533), "Aruba", "Aruba"),
+            Landskod::AX => ("ALA", Some(248), "Åland", "Åland Islands"),
+            Landskod::AZ => ("AZE", Some(31), "Azerbajdzjan", "Azerbaijan"),
+            Landskod::BA => ("BIH", Some(70), "Bosnien och Hercegovina", "Bosnia and Herzegovina"),
+            Landskod::BB => ("BRB", Some(52), "Barbados", "Barbados"),
+            Landskod::BD => ("BGD", Some(50), "Bangladesh", "Bangladesh"),
+            Landskod::BE => ("BEL", Some(56), "Belgien", "Belgium"),
+            Landskod::BF => ("BFA", Some(854), "Burkina Faso", "Burkina Faso"),
+            Landskod::BG => ("BGR", Some(100), "Bulgarien", "Bulgaria"),
+            Landskod::BH => ("BHR", Some(48), "Bahrain", "Bahrain"),
+            Landskod::BI => ("BDI", Some(108), "Burundi", "Burundi"),
+            Landskod::BJ => ("BEN", Some(204), "Benin", "Benin"),
+            Landskod::BL => ("BLM", Some(652), "Saint-Barthélemy", "Saint Barthélemy"),
+            Landskod::BM => ("BMU", Some(60), "Bermuda", "Bermuda"),
+            Landskod::BN => ("BRN", Some(96), "Brunei", "Brunei Darussalam"),
+            Landskod::BO => ("BOL", Some(68), "Bolivia", "Bolivia"),
+            Landskod::BQ => ("BES", Some(535), "Bonaire, Sint Eustatius och Saba", "Bonaire, Sint Eustatius and Saba"),
+            Landskod::BR => ("BRA", Some(76), "Brasilien", "Brazil"),
+            Landskod::BS => ("BHS", Some(44), "Bahamas", "Bahamas"),
+            Landskod::BT => ("BTN", Some(64), "Bhutan", "Bhutan"),
+            Landskod::BV => ("BVT", Some(74), "Bouvetön", "Bouvet Island"),
+            Landskod::BW => ("BWA", Some(72), "Botswana", "Botswana"),
+            Landskod::BY => ("BLR", Some(112), "Belarus", "Belarus"),
+            Landskod::BZ => ("BLZ", Some(84), "Belize", "Belize"),
+            Landskod::CA => ("CAN", Some(124), "Kanada", "Canada"),
+            Landskod::CC => ("CCK", Some(166), "Kokosöarna", "Cocos (Keeling) Islands"),
+            Landskod::CD => ("COD", Some(180), "Demokratiska republiken Kongo", "Democratic Republic of the Congo"),
+            Landskod::CF => ("CAF", Some(140), "Centralafrikanska republiken", "Central African Republic"),
+            Landskod::CG => ("COG", Some(178), "Kongo", "Congo"),
+            Landskod::CH => ("CHE", Some(756), "Schweiz", "Switzerland"),
+            Landskod::CI => ("CIV", Some(384), "Elfenbenskusten", "Côte d'Ivoire"),
+            Landskod::CK => ("COK", Some(184), "Cooköarna", "Cook Islands"),
+            Landskod::CL => ("CHL", Some(152), "Chile", "Chile"),
+            Landskod::CM => ("CMR", Some(120), "Kamerun", "Cameroon"),
+            Landskod::CN => ("CHN", Some(156), "Kina", "China"),
+            Landskod::CO => ("COL", Some(170), "Colombia", "Colombia"),
+            Landskod::CR => ("CRI", Some(188), "Costa Rica", "Costa Rica"),
+            Landskod::CU => ("CUB", Some(192), "Kuba", "Cuba"),
+            Landskod::CV => ("CPV", Some(132), "Kap Verde", "Cabo Verde"),
+            Landskod::CW => ("CUW", Some(531), "Curaçao", "Curaçao"),
+            Landskod::CX => ("CXR", Some(162), "Julön", "Christmas Island"),
+            Landskod::CY => ("CYP", Some(196), "Cypern", "Cyprus"),
+            Landskod::CZ => ("CZE", Some(203), "Tjeckien", "Czechia"),
+            Landskod::DE => ("DEU", Some(276), "Tyskland", "Germany"),
+            Landskod::DJ => ("DJI", Some(262), "Djibouti", "Djibouti"),
+            Landskod::DK => ("DNK", Some(208), "Danmark", "Denmark"),
+            Landskod::DM => ("DMA", Some(212), "Dominica", "Dominica"),
+            Landskod::DO => ("DOM", Some(214), "Dominikanska republiken", "Dominican Republic"),
+            Landskod::DZ => ("DZA", Some(12), "Algeriet", "Algeria"),
+            Landskod::EC => ("ECU", Some(218), "Ecuador", "Ecuador"),
+            Landskod::EE => ("EST", Some(233), "Estland", "Estonia"),
+            Landskod::EG => ("EGY", Some(818), "Egypten", "Egypt"),
+            Landskod::EH => ("ESH", Some(732), "Västsahara", "Western Sahara"),
+            Landskod::ER => ("ERI", Some(232), "Eritrea", "Eritrea"),
+            Landskod::ES => ("ESP", Some(724), "Spanien", "Spain"),
+            Landskod::ET => ("ETH", Some(231), "Etiopien", "Ethiopia"),
+            Landskod::FI => ("FIN", Some(246), "Finland", "Finland"),
+            Landskod::FJ => ("FJI", Some(242), "Fiji", "Fiji"),
+            Landskod::FK => ("FLK", Some(238), "Falklandsöarna", "Falkland Islands"),
+            Landskod::FM => ("FSM", Some(583), "Mikronesiska federationen", "Micronesia"),
+            Landskod::FO => ("FRO", Some(234), "Färöarna", "Faroe Islands"),
+            Landskod::FR => ("FRA", Some(250), "Frankrike", "France"),
+            Landskod::GA => ("GAB", Some(266), "Gabon", "Gabon"),
+            Landskod::GB => ("GBR", Some(826), "Storbritannien", "United Kingdom"),
+            Landskod::GD => ("GRD", Some(308), "Grenada", "Grenada"),
+            Landskod::GE => ("GEO", Some(268), "Georgien", "Georgia"),
+            Landskod::GF => ("GUF", Some(254), "Franska Guyana", "French Guiana"),
+            Landskod::GG => ("GGY", Some(831), "Guernsey", "Guernsey"),
+            Landskod::GH => ("GHA", Some(288), "Ghana", "Ghana"),
+            Landskod::GI => ("GIB", Some(292), "Gibraltar", "Gibraltar"),
+            Landskod::GL => ("GRL", Some(304), "Grönland", "Greenland"),
+            Landskod::GM => ("GMB", Some(270), "Gambia", "Gambia"),
+            Landskod::GN => ("GIN", Some(324), "Guinea", "Guinea"),
+            Landskod::GP => ("GLP", Some(312), "Guadeloupe", "Guadeloupe"),
+            Landskod::GQ => ("GNQ", Some(226), "Ekvatorialguinea", "Equatorial Guinea"),
+            Landskod::GR => ("GRC", Some(300), "Grekland", "Greece"),
+            Landskod::GS => ("SGS", Some(239), "Sydgeorgien och Sydsandwichöarna", "South Georgia and the South Sandwich Islands"),
+            Landskod::GT => ("GTM", Some(320), "Guatemala", "Guatemala"),
+            Landskod::GU => ("GUM", Some(316), "Guam", "Guam"),
+            Landskod::GW => ("GNB", Some(624), "Guinea-Bissau", "Guinea-Bissau"),
+            Landskod::GY => ("GUY", Some(328), "Guyana", "Guyana"),
+            Landskod::HK => ("HKG", Some(344), "Hongkong", "Hong Kong"),
+            Landskod::HM => ("HMD", Some(334), "Heard- och McDonaldöarna", "Heard Island and McDonald Islands"),
+            Landskod::HN => ("HND", Some(340), "Honduras", "Honduras"),
+            Landskod::HR => ("HRV", Some(191), "Kroatien", "Croatia"),
+            Landskod::HT => ("HTI", Some(332), "Haiti", "Haiti"),
+            Landskod::HU => ("HUN", Some(348), "Ungern", "Hungary"),
+            Landskod::ID => ("IDN", Some(360), "Indonesien", "Indonesia"),
+            Landskod::IE => ("IRL", Some(372), "Irland", "Ireland"),
+            Landskod::IL => ("ISR", Some(376), "Israel", "Israel"),
+            Landskod::IM => ("IMN", Some(833), "Isle of Man", "Isle of Man"),
+            Landskod::IN => ("IND", Some(356), "Indien", "India"),
+            Landskod::IO => ("IOT", Some(86), "Brittiska territoriet i Indiska oceanen", "British Indian Ocean Territory"),
+            Landskod::IQ => ("IRQ", Some(368), "Irak", "Iraq"),
+            Landskod::IR => ("IRN", Some(364), "Iran", "Iran"),
+            Landskod::IS => ("ISL", Some(352), "Island", "Iceland"),
+            Landskod::IT => ("ITA", Some(380), "Italien", "Italy"),
+            Landskod::JE => ("JEY", Some(832), "Jersey", "Jersey"),
+            Landskod::JM => ("JAM", Some(388), "Jamaica", "Jamaica"),
+            Landskod::JO => ("JOR", Some(400), "Jordanien", "Jordan"),
+            Landskod::JP => ("JPN", Some(392), "Japan", "Japan"),
+            Landskod::KE => ("KEN", Some(404), "Kenya", "Kenya"),
+            Landskod::KG => ("KGZ", Some(417), "Kirgizistan", "Kyrgyzstan"),
+            Landskod::KH => ("KHM", Some(116), "Kambodja", "Cambodia"),
+            Landskod::KI => ("KIR", Some(296), "Kiribati", "Kiribati"),
+            Landskod::KM => ("COM", Some(174), "Komorerna", "Comoros"),
+            Landskod::KN => ("KNA", Some(659), "Saint Kitts och Nevis", "Saint Kitts and Nevis"),
+            Landskod::KP => ("PRK", Some(408), "Nordkorea", "North Korea"),
+            Landskod::KR => ("KOR", Some(410), "Sydkorea", "South Korea"),
+            Landskod::KW => ("KWT", Some(414), "Kuwait", "Kuwait"),
+            Landskod::KY => ("CYM", Some(136), "Caymanöarna", "Cayman Islands"),
+            Landskod::KZ => ("KAZ", Some(398), "Kazakstan", "Kazakhstan"),
+            Landskod::LA => ("LAO", Some(418), "Laos", "Laos"),
+            Landskod::LB => ("LBN", Some(422), "Libanon", "Lebanon"),
+            Landskod::LC => ("LCA", Some(662), "Saint Lucia", "Saint Lucia"),
+            Landskod::LI => ("LIE", Some(438), "Liechtenstein", "Liechtenstein"),
+            Landskod::LK => ("LKA", Some(144), "Sri Lanka", "Sri Lanka"),
+            Landskod::LR => ("LBR", Some(430), "Liberia", "Liberia"),
+            Landskod::LS => ("LSO", Some(426), "Lesotho", "Lesotho"),
+            Landskod::LT => ("LTU", Some(440), "Litauen", "Lithuania"),
+            Landskod::LU => ("LUX", Some(442), "Luxemburg", "Luxembourg"),
+            Landskod::LV => ("LVA", Some(428), "Lettland", "Latvia"),
+            Landskod::LY => ("LBY", Some(434), "Libyen", "Libya"),
+            Landskod::MA => ("MAR", Some(504), "Marocko", "Morocco"),
+            Landskod::MC => ("MCO", Some(492), "Monaco", "Monaco"),
+            Landskod::MD => ("MDA", Some(498), "Moldavien", "Moldova"),
+            Landskod::ME => ("MNE", Some(499), "Montenegro", "Montenegro"),
+            Landskod::MF => ("MAF", Some(663), "Saint-Martin", "Saint Martin"),
+            Landskod::MG => ("MDG", Some(450), "Madagaskar", "Madagascar"),
+            Landskod::MH => ("MHL", Some(584), "Marshallöarna", "Marshall Islands"),
+            Landskod::MK => ("MKD", Some(807), "Nordmakedonien", "North Macedonia"),
+            Landskod::ML => ("MLI", Some(466), "Mali", "Mali"),
+            Landskod::MM => ("MMR", Some(104), "Myanmar", "Myanmar"),
+            Landskod::MN => ("MNG", Some(496), "Mongoliet", "Mongolia"),
+            Landskod::MO => ("MAC", Some(446), "Macao", "Macao"),
+            Landskod::MP => ("MNP", Some(580), "Nordmarianerna", "Northern Mariana Islands"),
+            Landskod::MQ => ("MTQ", Some(474), "Martinique", "Martinique"),
+            Landskod::MR => ("MRT", Some(478), "Mauretanien", "Mauritania"),
+            Landskod::MS => ("MSR", Some(500), "Montserrat", "Montserrat"),
+            Landskod::MT => ("MLT", Some(470), "Malta", "Malta"),
+            Landskod::MU => ("MUS", Some(480), "Mauritius", "Mauritius"),
+            Landskod::MV => ("MDV", Some(462), "Maldiverna", "Maldives"),
+            Landskod::MW => ("MWI", Some(454), "Malawi", "Malawi"),
+            Landskod::MX => ("MEX", Some(484), "Mexiko", "Mexico"),
+            Landskod::MY => ("MYS", Some(458), "Malaysia", "Malaysia"),
+            Landskod::MZ => ("MOZ", Some(508), "Moçambique", "Mozambique"),
+            Landskod::NA => ("NAM", Some(516), "Namibia", "Namibia"),
+            Landskod::NC => ("NCL", Some(540), "Nya Kaledonien", "New Caledonia"),
+            Landskod::NE => ("NER", Some(562), "Niger", "Niger"),
+            Landskod::NF => ("NFK", Some(574), "Norfolkön", "Norfolk Island"),
+            Landskod::NG => ("NGA", Some(566), "Nigeria", "Nigeria"),
+            Landskod::NI => ("NIC", Some(558), "Nicaragua", "Nicaragua"),
+            Landskod::NL => ("NLD", Some(528), "Nederländerna", "Netherlands"),
+            Landskod::NO => ("NOR", Some(578), "Norge", "Norway"),
+            Landskod::NP => ("NPL", Some(524), "Nepal", "Nepal"),
+            Landskod::NR => ("NRU", Some(520), "Nauru", "Nauru"),
+            Landskod::NU => ("NIU", Some(570), "Niue", "Niue"),
+            Landskod::NZ => ("NZL", Some(554), "Nya Zeeland", "New Zealand"),
+            Landskod::OM => ("OMN", Some(512), "Oman", "Oman"),
+            Landskod::PA => ("PAN", Some(591), "Panama", "Panama"),
+            Landskod::PE => ("PER", Some(604), "Peru", "Peru"),
+            Landskod::PF => ("PYF", Some(258), "Franska Polynesien", "French Polynesia"),
+            Landskod::PG => ("PNG", Some(598), "Papua Nya Guinea", "Papua New Guinea"),
+            Landskod::PH => ("PHL", Some(608), "Filippinerna", "Philippines"),
+            Landskod::PK => ("PAK", Some(586), "Pakistan", "Pakistan"),
+            Landskod::PL => ("POL", Some(616), "Polen", "Poland"),
+            Landskod::PM => ("SPM", Some(666), "Saint-Pierre och Miquelon", "Saint Pierre and Miquelon"),
+            Landskod::PN => ("PCN", Some(612), "Pitcairnöarna", "Pitcairn"),
+            Landskod::PR => ("PRI", Some(630), "Puerto Rico", "Puerto Rico"),
+            Landskod::PS => ("PSE", Some(275), "Palestina", "Palestine"),
+            Landskod::PT => ("PRT", Some(620), "Portugal", "Portugal"),
+            Landskod::PW => ("PLW", Some(585), "Palau", "Palau"),
+            Landskod::PY => ("PRY", Some(600), "Paraguay", "Paraguay"),
+            Landskod::QA => ("QAT", Some(634), "Qatar", "Qatar"),
+            Landskod::RE => ("REU", Some(638), "Réunion", "Réunion"),
+            Landskod::RO => ("ROU", Some(642), "Rumänien", "Romania"),
+            Landskod::RS => ("SRB", Some(688), "Serbien", "Serbia"),
+            Landskod::RU => ("RUS", Some(643), "Ryssland", "Russia"),
+            Landskod::RW => ("RWA", Some(646), "Rwanda", "Rwanda"),
+            Landskod::SA => ("SAU", Some(682), "Saudiarabien", "Saudi Arabia"),
+            Landskod::SB => ("SLB", Some(90), "Salomonöarna", "Solomon Islands"),
+            Landskod::SC => ("SYC", Some(690), "Seychellerna", "Seychelles"),
+            Landskod::SD => ("SDN", Some(729), "Sudan", "Sudan"),
+            Landskod::SE => ("SWE", Some(752), "Sverige", "Sweden"),
+            Landskod::SG => ("SGP", Some(702), "Singapore", "Singapore"),
+            Landskod::SH => ("SHN", Some(654), "Sankta Helena", "Saint Helena"),
+            Landskod::SI => ("SVN", Some(705), "Slovenien", "Slovenia"),
+            Landskod::SJ => ("SJM", Some(744), "Svalbard och Jan Mayen", "Svalbard and Jan Mayen"),
+            Landskod::SK => ("SVK", Some(703), "Slovakien", "Slovakia"),
+            Landskod::SL => ("SLE", Some(694), "Sierra Leone", "Sierra Leone"),
+            Landskod::SM => ("SMR", Some(674), "San Marino", "San Marino"),
+            Landskod::SN => ("SEN", Some(686), "Senegal", "Senegal"),
+            Landskod::SO => ("SOM", Some(706), "Somalia", "Somalia"),
+            Landskod::SR => ("SUR", Some(740), "Surinam", "Suriname"),
+            Landskod::SS => ("SSD", Some(728), "Sydsudan", "South Sudan"),
+            Landskod::ST => ("STP", Some(678), "São Tomé och Príncipe", "Sao Tome and Principe"),
+            Landskod::SV => ("SLV", Some(222), "El Salvador", "El Salvador"),
+            Landskod::SX => ("SXM", Some(534), "Sint Maarten", "Sint Maarten"),
+            Landskod::SY => ("SYR", Some(760), "Syrien", "Syria"),
+            Landskod::SZ => ("SWZ", Some(748), "Eswatini", "Eswatini"),
+            Landskod::TC => ("TCA", Some(796), "Turks- och Caicosöarna", "Turks and Caicos Islands"),
+            Landskod::TD => ("TCD", Some(148), "Tchad", "Chad"),
+            Landskod::TF => ("ATF", Some(260), "Franska sydterritorierna", "French Southern Territories"),
+            Landskod::TG => ("TGO", Some(768), "Togo", "Togo"),
+            Landskod::TH => ("THA", Some(764), "Thailand", "Thailand"),
+            Landskod::TJ => ("TJK", Some(762), "Tadzjikistan", "Tajikistan"),
+            Landskod::TK => ("TKL", Some(772), "Tokelau", "Tokelau"),
+            Landskod::TL => ("TLS", Some(626), "Östtimor", "Timor-Leste"),
+            Landskod::TM => ("TKM", Some(795), "Turkmenistan", "Turkmenistan"),
+            Landskod::TN => ("TUN", Some(788), "Tunisien", "Tunisia"),
+            Landskod::TO => ("TON", Some(776), "Tonga", "Tonga"),
+            Landskod::TR => ("TUR", Some(792), "Turkiet", "Türkiye"),
+            Landskod::TT => ("TTO", Some(780), "Trinidad och Tobago", "Trinidad and Tobago"),
+            Landskod::TV => ("TUV", Some(798), "Tuvalu", "Tuvalu"),
+            Landskod::TW => ("TWN", Some(158), "Taiwan", "Taiwan"),
+            Landskod::TZ => ("TZA", Some(834), "Tanzania", "Tanzania"),
+            Landskod::UA => ("UKR", Some(804), "Ukraina", "Ukraine"),
+            Landskod::UG => ("UGA", Some(800), "Uganda", "Uganda"),
+            Landskod::UM => ("UMI", Some(581), "Förenta staternas mindre öar i Oceanien och Västindien", "United States Minor Outlying Islands"),
+            Landskod::US => ("USA", Some(840), "USA", "United States"),
+            Landskod::UY => ("URY", Some(858), "Uruguay", "Uruguay"),
+            Landskod::UZ => ("UZB", Some(860), "Uzbekistan", "Uzbekistan"),
+            Landskod::VA => ("VAT", Some(336), "Vatikanstaten", "Holy See"),
+            Landskod::VC => ("VCT", Some(670), "Saint Vincent och Grenadinerna", "Saint Vincent and the Grenadines"),
+            Landskod::VE => ("VEN", Some(862), "Venezuela", "Venezuela"),
+            Landskod::VG => ("VGB", Some(92), "Brittiska Jungfruöarna", "British Virgin Islands"),
+            Landskod::VI => ("VIR", Some(850), "Amerikanska Jungfruöarna", "United States Virgin Islands"),
+            Landskod::VN => ("VNM", Some(704), "Vietnam", "Viet Nam"),
+            Landskod::VU => ("VUT", Some(548), "Vanuatu", "Vanuatu"),
+            Landskod::WF => ("WLF", Some(876), "Wallis- och Futunaöarna", "Wallis and Futuna"),
+            Landskod::WS => ("WSM", Some(882), "Samoa", "Samoa"),
+            Landskod::XK => ("XKX", None, "Kosovo", "Kosovo"),
+            Landskod::YE => ("YEM", Some(887), "Jemen", "Yemen"),
+            Landskod::YT => ("MYT", Some(175), "Mayotte", "Mayotte"),
+            Landskod::ZA => ("ZAF", Some(710), "Sydafrika", "South Africa"),
+            Landskod::ZM => ("ZMB", Some(894), "Zambia", "Zambia"),
+            Landskod::ZW => ("ZWE", Some(716), "Zimbabwe", "Zimbabwe"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IdentitetsbeteckningForPerson<'a>(Cow<'a, str>);
 
 impl<'a, 'b: 'a> Readable<'a, 'b> for IdentitetsbeteckningForPerson<'a> {
@@ -880,7 +1400,7 @@ impl TryFrom<&str> for IdentitetsbeteckningForPerson<'_> {
         {
             return Ok(IdentitetsbeteckningForPerson(Cow::Owned(value.to_string())));
         }
-        return Err("Not valid Identitetsbeteckning");
+        Err("Not valid Identitetsbeteckning")
     }
 }
 
@@ -895,3 +1415,343 @@ impl Writable for IdentitetsbeteckningForPerson<'_> {
         Some(self.0.to_string())
     }
 }
+
+/// International Securities Identification Number, two letter country prefix, nine alphanumeric characters and a check digit
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isin<'a>(Cow<'a, str>);
+
+impl Isin<'_> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The country prefix, `None` for prefixes that are not country codes such as "XS"
+    pub fn landskod(&self) -> Option<Landskod> {
+        Landskod::try_from(&self.0[..2]).ok()
+    }
+
+    fn check_digit(isin: &str) -> u32 {
+        let digits: Vec<u32> = isin[..11]
+            .chars()
+            .flat_map(|c| {
+                let value = c.to_digit(36).expect("checked to be alphanumeric");
+                if value < 10 {
+                    vec![value]
+                } else {
+                    vec![value / 10, value % 10]
+                }
+            })
+            .collect();
+        let sum: u32 = digits
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &d)| {
+                if i % 2 == 0 {
+                    let doubled = d * 2;
+                    doubled / 10 + doubled % 10
+                } else {
+                    d
+                }
+            })
+            .sum();
+        (10 - sum % 10) % 10
+    }
+}
+
+impl Display for Isin<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for Isin<'_> {
+    type Error = IsinError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.len() != 12 {
+            return Err(IsinError::InvalidLength(value.len()));
+        }
+        let bytes = value.as_bytes();
+        if !bytes[..2].iter().all(u8::is_ascii_uppercase)
+            || !bytes[2..11]
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            || !bytes[11].is_ascii_digit()
+        {
+            return Err(IsinError::InvalidFormat(value.to_string()));
+        }
+        let expected = Isin::check_digit(value);
+        let found = (bytes[11] - b'0') as u32;
+        if expected != found {
+            return Err(IsinError::InvalidCheckDigit { expected, found });
+        }
+        Ok(Isin(Cow::Owned(value.to_string())))
+    }
+}
+
+impl<'a, 'b: 'a> Readable<'a, 'b> for Isin<'a> {
+    fn get_str(data: Cow<'b, str>) -> Result<Self, Error> {
+        data.as_ref()
+            .try_into()
+            .map_err(|e: IsinError| Error::UnexpectedToken(e.to_string()))
+    }
+}
+
+impl Writable for Isin<'_> {
+    fn get_str(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IsinError {
+    InvalidLength(usize),
+    InvalidFormat(String),
+    InvalidCheckDigit { expected: u32, found: u32 },
+}
+
+impl Display for IsinError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            IsinError::InvalidLength(length) => {
+                write!(f, "ISIN must be 12 characters, got {}", length)
+            }
+            IsinError::InvalidFormat(isin) => write!(
+                f,
+                "{} is not an ISIN, expected a country prefix, nine alphanumeric characters and a check digit",
+                isin
+            ),
+            IsinError::InvalidCheckDigit { expected, found } => write!(
+                f,
+                "ISIN check digit is {}, expected {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::Fields;
+    use crate::validation::Constraints;
+    use kontrolluppgift_macros::KontrolluppgiftChoice;
+
+    #[test]
+    fn landskod_metadata() {
+        assert_eq!(Landskod::iter().count(), 251);
+        assert_eq!(Landskod::SE.alpha3(), "SWE");
+        assert_eq!(Landskod::SE.numerisk_kod(), Some(752));
+        assert_eq!(Landskod::SE.svenskt_namn(), "Sverige");
+        assert_eq!(Landskod::SE.engelskt_namn(), "Sweden");
+        assert_eq!(Landskod::from_alpha3("NOR"), Some(Landskod::NO));
+        assert_eq!(Landskod::from_numerisk_kod(246), Some(Landskod::FI));
+        for landskod in Landskod::iter() {
+            assert_eq!(Landskod::from_alpha3(landskod.alpha3()), Some(*landskod));
+        }
+
+        assert_eq!(Landskod::iter().filter(|l| l.is_eu()).count(), 27);
+        assert_eq!(Landskod::iter().filter(|l| l.is_ees()).count(), 30);
+        assert!(Landskod::NO.is_ees() && !Landskod::NO.is_eu());
+        assert!(Landskod::FO.is_nordisk() && !Landskod::FO.is_ees());
+        assert!(!Landskod::GB.is_ees());
+    }
+
+    /// The contents of every example file from Skatteverket
+    pub(crate) fn example_files() -> Vec<(String, String)> {
+        let mut files: Vec<_> = std::fs::read_dir(".")
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.starts_with("EXEMPELFIL") && name.ends_with(".xml"))
+            .map(|name| {
+                let xml = std::fs::read_to_string(&name).unwrap();
+                (name, xml)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn strict_read_requires_sequence_order() {
+        let files = example_files();
+        assert_eq!(files.len(), 6);
+        for (name, xml) in &files {
+            let parsed = from_str_strict(xml).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert!(from_str_strict(&to_string(&parsed).unwrap()).is_ok());
+        }
+
+        let xml = std::fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let reordered = xml.replace("<ku:Inkomstar faltkod=\"203\">2022</ku:Inkomstar>", "");
+        let reordered = reordered.replace(
+            "<ku:KontantBruttolonMm",
+            "<ku:Inkomstar faltkod=\"203\">2022</ku:Inkomstar><ku:KontantBruttolonMm",
+        );
+        let parsed = from_str(&reordered).unwrap();
+        assert_eq!(parsed, from_str(&xml).unwrap());
+        assert!(matches!(
+            from_str_strict(&reordered),
+            Err(Error::OutOfSequence { element, reading })
+                if element == "KontantBruttolonMm" && reading == "KU10"
+        ));
+
+        let written = to_string(&parsed).unwrap();
+        assert!(from_str_strict(&written).is_ok());
+    }
+
+    #[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+    #[ku(name("Upprepad"))]
+    struct Upprepad<'a> {
+        #[ku(name(b"Belopp"), code("001"), min(1), max(3), seq(1))]
+        belopp: Vec<i32>,
+        #[ku(name(b"Namn"), code("002"), seq(2))]
+        namn: Option<Cow<'a, str>>,
+        #[ku(choice(true), seq(3))]
+        betalning: Option<Betalning<'a>>,
+        #[ku(name(b"Post"), inner_ty(true), max(2), seq(4))]
+        poster: Vec<Post<'a>>,
+    }
+
+    #[derive(Debug, PartialEq, KontrolluppgiftChoice)]
+    enum Betalning<'a> {
+        #[ku(name(b"Kontant"), code("003"))]
+        Kontant(i32),
+        #[ku(name(b"Konto"), inner_ty(true))]
+        Konto(Konto<'a>),
+    }
+
+    #[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+    #[ku(name("Konto"))]
+    struct Konto<'a> {
+        #[ku(name(b"Kontonummer"), code("004"), required(true), seq(1))]
+        kontonummer: Cow<'a, str>,
+    }
+
+    #[derive(Debug, PartialEq, KontrolluppgiftRead, KontrolluppgiftWrite)]
+    #[ku(name("Post"))]
+    struct Post<'a> {
+        #[ku(name(b"Text"), code("005"), seq(1))]
+        text: Option<Cow<'a, str>>,
+    }
+
+    fn read_upprepad(xml: &str, strict: bool) -> Result<Upprepad<'_>, Error> {
+        let mut reader = NsReader::from_str(xml);
+        reader.expand_empty_elements(true);
+        loop {
+            if let Event::Start(element) = reader.read_event()? {
+                return Upprepad::read(&mut reader, &element, strict);
+            }
+        }
+    }
+
+    fn write_upprepad(upprepad: &Upprepad) -> String {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        upprepad.write(&mut writer).unwrap();
+        String::from_utf8(writer.into_inner().into_inner()).unwrap()
+    }
+
+    #[test]
+    fn repeated_elements_and_choices_are_read_and_written() {
+        let upprepad = Upprepad {
+            belopp: vec![1, 2],
+            namn: Some("Namn".into()),
+            betalning: Some(Betalning::Konto(Konto {
+                kontonummer: "1234".into(),
+            })),
+            poster: vec![
+                Post {
+                    text: Some("Första".into()),
+                },
+                Post { text: None },
+            ],
+        };
+        let written = write_upprepad(&upprepad);
+        assert_eq!(
+            written,
+            "<Upprepad><Belopp faltkod=\"001\">1</Belopp><Belopp faltkod=\"001\">2</Belopp>\
+             <Namn faltkod=\"002\">Namn</Namn>\
+             <Konto><Kontonummer faltkod=\"004\">1234</Kontonummer></Konto>\
+             <Post><Text faltkod=\"005\">Första</Text></Post><Post></Post></Upprepad>"
+        );
+        assert_eq!(read_upprepad(&written, true).unwrap(), upprepad);
+
+        let kontant = Upprepad {
+            belopp: vec![3],
+            namn: None,
+            betalning: Some(Betalning::Kontant(100)),
+            poster: vec![],
+        };
+        let written = write_upprepad(&kontant);
+        assert!(written.contains("<Kontant faltkod=\"003\">100</Kontant>"));
+        assert_eq!(read_upprepad(&written, true).unwrap(), kontant);
+
+        let belopp = Upprepad::field_by_name("Belopp").unwrap();
+        assert_eq!((belopp.min_occurs, belopp.max_occurs), (1, Some(3)));
+        let poster = Upprepad::field_by_name("Post").unwrap();
+        assert_eq!((poster.min_occurs, poster.max_occurs), (0, Some(2)));
+        assert_eq!(Upprepad::field_by_code("003").unwrap().name, "Kontant");
+    }
+
+    #[test]
+    fn occurrences_are_checked() {
+        assert!(matches!(
+            read_upprepad("<Upprepad><Namn faltkod=\"002\">Namn</Namn></Upprepad>", false),
+            Err(Error::MissingElement { missing, .. }) if missing == "Belopp"
+        ));
+
+        let belopp = "<Belopp faltkod=\"001\">1</Belopp>";
+        let xml = format!("<Upprepad>{}</Upprepad>", belopp.repeat(4));
+        assert!(matches!(
+            read_upprepad(&xml, false),
+            Err(Error::TooManyElements { element, reading, max: 3 })
+                if element == "Belopp" && reading == "Upprepad"
+        ));
+
+        let xml = format!(
+            "<Upprepad>{}{}</Upprepad>",
+            belopp,
+            "<Post></Post>".repeat(3)
+        );
+        assert!(matches!(
+            read_upprepad(&xml, false),
+            Err(Error::TooManyElements { element, max: 2, .. }) if element == "Post"
+        ));
+
+        let xml = format!(
+            "<Upprepad>{}<Namn faltkod=\"002\">Namn</Namn>{}</Upprepad>",
+            belopp, belopp
+        );
+        assert_eq!(read_upprepad(&xml, false).unwrap().belopp, vec![1, 1]);
+        assert!(matches!(
+            read_upprepad(&xml, true),
+            Err(Error::OutOfSequence { element, reading })
+                if element == "Belopp" && reading == "Upprepad"
+        ));
+
+        // the writer does not check the occurrences, validation does
+        let upprepad = Upprepad {
+            belopp: vec![],
+            namn: None,
+            betalning: None,
+            poster: vec![
+                Post { text: None },
+                Post { text: None },
+                Post { text: None },
+            ],
+        };
+        assert!(read_upprepad(&write_upprepad(&upprepad), false).is_err());
+        let mut violations = vec![];
+        upprepad.check_constraints(&mut violations);
+        assert_eq!(
+            violations.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec![
+                "[001] Belopp occurs 0 times, at least 1 are required",
+                "Post occurs 3 times, at most 2 are allowed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::key::check_duplicates;
+use crate::validation::Violation;
+use crate::{Avsandare, Kontrolluppgift};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// How [merge] decides if the files can be combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Every file must have the same Avsandare and Blankettgemensamt, apart from when the file was
+    /// created (Skapad), the Skapad of the first file is used
+    Identical,
+    /// Every file must have the same UppgiftslamnarePersOrgnr, the Avsandare and
+    /// Blankettgemensamt of the first file are used
+    SameUppgiftslamnare,
+}
+
+/// Why [merge] could not combine the files
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeError {
+    NoFiles,
+    /// The file at this index can not be combined with the first file
+    Incompatible(usize),
+    /// Blanketter with the same key in the merged file
+    Duplicates(Vec<Violation>),
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MergeError::NoFiles => write!(f, "there are no files to merge"),
+            MergeError::Incompatible(index) => write!(
+                f,
+                "file {} has a different Avsandare or Blankettgemensamt than the first file",
+                index
+            ),
+            MergeError::Duplicates(violations) => {
+                write!(f, "the merged file has duplicates: ")?;
+                let violations: Vec<_> = violations.iter().map(|v| v.to_string()).collect();
+                f.write_str(&violations.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// Combines the Blanketter of several files into one, in the order of the files, numbered from 1
+pub fn merge<'a>(
+    files: impl IntoIterator<Item = Kontrolluppgift<'a>>,
+    policy: MergePolicy,
+) -> Result<Kontrolluppgift<'a>, MergeError> {
+    let mut files = files.into_iter();
+    let mut merged = files.next().ok_or(MergeError::NoFiles)?;
+    for (index, file) in files.enumerate() {
+        let compatible = match policy {
+            MergePolicy::Identical => {
+                same_avsandare(&file.avsandare, &merged.avsandare)
+                    && file.blankettgemensamt == merged.blankettgemensamt
+            }
+            MergePolicy::SameUppgiftslamnare => {
+                file.blankettgemensamt
+                    .uppgiftslamnare
+                    .uppgiftslamnare_pers_orgnr
+                    == merged
+                        .blankettgemensamt
+                        .uppgiftslamnare
+                        .uppgiftslamnare_pers_orgnr
+            }
+        };
+        if !compatible {
+            return Err(MergeError::Incompatible(index + 1));
+        }
+        merged.blanketter.extend(file.blanketter);
+    }
+    for (b, nummer) in merged.blanketter.iter_mut().zip(1..) {
+        b.nummer = nummer;
+    }
+
+    let mut violations = vec![];
+    check_duplicates(&merged.blanketter, &mut violations);
+    if !violations.is_empty() {
+        return Err(MergeError::Duplicates(violations));
+    }
+    Ok(merged)
+}
+
+/// Compares everything but Skapad, as files created separately are created at different times
+fn same_avsandare(a: &Avsandare, b: &Avsandare) -> bool {
+    a.programnamn == b.programnamn
+        && a.organisationsnummer == b.organisationsnummer
+        && a.teknisk_kontaktperson == b.teknisk_kontaktperson
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::{KontrolluppgiftForm, KontrolluppgiftKind};
+    use crate::from_str;
+    use std::fs;
+
+    #[test]
+    fn files_are_merged() {
+        let ku10 = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let ku21 = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFTER RÄNTA, UTDELNING M.M. KU21 FÖR_2022.xml",
+        )
+        .unwrap();
+        let files = || vec![from_str(&ku10).unwrap(), from_str(&ku21).unwrap()];
+        assert_eq!(
+            merge(files(), MergePolicy::SameUppgiftslamnare),
+            Err(MergeError::Incompatible(1))
+        );
+
+        let files = || {
+            let mut files = files();
+            files[1]
+                .blankettgemensamt
+                .uppgiftslamnare
+                .uppgiftslamnare_pers_orgnr = "165021680250".into();
+            files
+        };
+        assert_eq!(
+            merge(files(), MergePolicy::Identical),
+            Err(MergeError::Incompatible(1))
+        );
+        let merged = merge(files(), MergePolicy::SameUppgiftslamnare).unwrap();
+        let blanketter: Vec<_> = merged
+            .blanketter
+            .iter()
+            .map(|b| (b.nummer, b.blankettinnehall.kind()))
+            .collect();
+        assert_eq!(
+            blanketter,
+            vec![
+                (1, KontrolluppgiftKind::KU10),
+                (2, KontrolluppgiftKind::KU21)
+            ]
+        );
+        assert_eq!(merged.avsandare, from_str(&ku10).unwrap().avsandare);
+
+        let mut later = from_str(&ku10).unwrap();
+        later.avsandare.skapad = "2023-01-31T09:00:00".into();
+        *later.blanketter[0]
+            .blankettinnehall
+            .specifikationsnummer_mut() = 2;
+        let merged = merge(
+            vec![from_str(&ku10).unwrap(), later],
+            MergePolicy::Identical,
+        )
+        .unwrap();
+        assert_eq!(merged.blanketter.len(), 2);
+        assert_eq!(merged.avsandare, from_str(&ku10).unwrap().avsandare);
+
+        let twice = vec![from_str(&ku10).unwrap(), from_str(&ku10).unwrap()];
+        assert!(matches!(
+            merge(twice, MergePolicy::Identical),
+            Err(MergeError::Duplicates(violations)) if violations.len() == 1
+        ));
+        assert_eq!(
+            merge(vec![], MergePolicy::Identical),
+            Err(MergeError::NoFiles)
+        );
+        let error: Box<dyn std::error::Error> =
+            Box::new(merge(vec![], MergePolicy::Identical).unwrap_err());
+        assert_eq!(error.to_string(), "there are no files to merge");
+    }
+}
@@ -0,0 +1,170 @@
+/// Description of a single field, generated from its `#[ku(...)]` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldMeta {
+    /// The name of the xml element
+    pub name: &'static str,
+    /// The name of the field on the rust struct
+    pub field: &'static str,
+    pub faltkod: Option<&'static str>,
+    /// The rust type of the value, without the surrounding `Option` on fields that are not required
+    pub ty: &'static str,
+    pub required: bool,
+    /// An amount in kronor, declared with `amount` in the `ku` attribute
+    pub amount: bool,
+    /// How many times the element must occur
+    pub min_occurs: usize,
+    /// How many times the element may occur, `None` when unbounded
+    pub max_occurs: Option<usize>,
+    /// The fields of the nested element, for fields holding another struct
+    pub children: Option<&'static [FieldMeta]>,
+}
+
+/// Implemented by every struct deriving `KontrolluppgiftRead`
+pub trait Fields {
+    /// The name of the xml element
+    const ELEMENT: &'static str;
+    /// Every field, in the order of the xsd sequence
+    const FIELDS: &'static [FieldMeta];
+
+    /// Finds a field by faltkod, searching nested elements as well
+    fn field_by_code(faltkod: &str) -> Option<&'static FieldMeta> {
+        find_by_code(Self::FIELDS, faltkod)
+    }
+
+    /// Finds a field directly on this element by its xml element name
+    fn field_by_name(name: &str) -> Option<&'static FieldMeta> {
+        Self::FIELDS.iter().find(|f| f.name == name)
+    }
+}
+
+/// Whether the elements of the choices use no name or faltkod of the other fields of the struct,
+/// or of another choice. Evaluated at compile time by the `KontrolluppgiftRead` derive
+pub const fn choices_are_unique(
+    names: &[&str],
+    faltkoder: &[&str],
+    choices: &[&[FieldMeta]],
+) -> bool {
+    let mut c = 0;
+    while c < choices.len() {
+        let mut v = 0;
+        while v < choices[c].len() {
+            let variant = &choices[c][v];
+            if contains(names, variant.name) {
+                return false;
+            }
+            if let Some(faltkod) = variant.faltkod {
+                if contains(faltkoder, faltkod) {
+                    return false;
+                }
+            }
+            let mut other = c + 1;
+            while other < choices.len() {
+                let mut o = 0;
+                while o < choices[other].len() {
+                    let same_faltkod = match (variant.faltkod, choices[other][o].faltkod) {
+                        (Some(a), Some(b)) => str_eq(a, b),
+                        _ => false,
+                    };
+                    if str_eq(variant.name, choices[other][o].name) || same_faltkod {
+                        return false;
+                    }
+                    o += 1;
+                }
+                other += 1;
+            }
+            v += 1;
+        }
+        c += 1;
+    }
+    true
+}
+
+const fn contains(values: &[&str], value: &str) -> bool {
+    let mut i = 0;
+    while i < values.len() {
+        if str_eq(values[i], value) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+fn find_by_code(fields: &'static [FieldMeta], faltkod: &str) -> Option<&'static FieldMeta> {
+    fields.iter().find_map(|f| {
+        if f.faltkod == Some(faltkod) {
+            Some(f)
+        } else {
+            f.children.and_then(|c| find_by_code(c, faltkod))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ku10::KU10Type;
+
+    #[test]
+    fn fields_are_described() {
+        assert_eq!(KU10Type::ELEMENT, "KU10");
+
+        let inkomstar = KU10Type::field_by_code("203").unwrap();
+        assert_eq!(inkomstar.name, "Inkomstar");
+        assert_eq!(inkomstar.field, "inkomstar");
+        assert_eq!(inkomstar.ty, "Cow<'a, str>");
+        assert!(inkomstar.required);
+        assert_eq!(inkomstar.min_occurs, 1);
+        assert_eq!(inkomstar.max_occurs, Some(1));
+
+        let bostad_smahus = KU10Type::field_by_name("BostadSmahus").unwrap();
+        assert_eq!(bostad_smahus.faltkod, Some("041"));
+        assert_eq!(bostad_smahus.ty, "bool");
+        assert!(!bostad_smahus.required);
+        assert_eq!(bostad_smahus.min_occurs, 0);
+
+        let inkomsttagare = KU10Type::field_by_name("InkomsttagareKU10").unwrap();
+        assert_eq!(inkomsttagare.faltkod, None);
+        assert!(inkomsttagare.children.is_some());
+
+        let tin = KU10Type::field_by_code("252").unwrap();
+        assert_eq!(tin.name, "TIN");
+        assert_eq!(tin.ty, "Cow<'a, str>");
+    }
+
+    #[test]
+    fn choices_must_not_repeat_an_element() {
+        let variant = |name, faltkod| FieldMeta {
+            name,
+            field: name,
+            faltkod,
+            ty: "i32",
+            required: false,
+            amount: false,
+            min_occurs: 0,
+            max_occurs: Some(1),
+            children: None,
+        };
+        let kontant = [variant("Kontant", Some("003"))];
+        let konto = [variant("Konto", None)];
+        assert!(choices_are_unique(&["Namn"], &["002"], &[&kontant, &konto]));
+        assert!(!choices_are_unique(&["Namn"], &["003"], &[&kontant]));
+        assert!(!choices_are_unique(&["Konto"], &[], &[&kontant, &konto]));
+        assert!(!choices_are_unique(&[], &[], &[&konto, &konto]));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::form::KontrolluppgiftForm;
+use crate::inkomsttagare::Inkomsttagare;
+use crate::Kontrolluppgift;
+use std::collections::HashSet;
+
+type Predicate<'p> = Box<dyn Fn(&dyn Inkomsttagare) -> bool + 'p>;
+
+/// Removes the address and other sensitive fields of inkomsttagare with skyddad identitet,
+/// apply it to a [Kontrolluppgift] before writing it
+pub struct RedactionPolicy<'p> {
+    is_protected: Predicate<'p>,
+}
+
+/// A Blankett that had fields removed by a [RedactionPolicy]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redaction {
+    pub blankett: i64,
+    /// The faltkoder of the fields that were removed
+    pub faltkoder: Vec<&'static str>,
+}
+
+impl<'p> RedactionPolicy<'p> {
+    /// Protects every inkomsttagare the predicate returns true for
+    pub fn new(is_protected: impl Fn(&dyn Inkomsttagare) -> bool + 'p) -> Self {
+        RedactionPolicy {
+            is_protected: Box::new(is_protected),
+        }
+    }
+
+    /// Protects the inkomsttagare identified by any of the identities in Inkomsttagare
+    pub fn protected_identities(identities: HashSet<String>) -> Self {
+        Self::new(move |inkomsttagare| {
+            inkomsttagare
+                .inkomsttagare()
+                .is_some_and(|id| identities.contains(&String::from(id)))
+        })
+    }
+
+    /// Removes the sensitive fields of every protected inkomsttagare, returns a report of the
+    /// Blanketter that had fields removed
+    pub fn apply(&self, kontrolluppgift: &mut Kontrolluppgift) -> Vec<Redaction> {
+        kontrolluppgift
+            .blanketter
+            .iter_mut()
+            .filter_map(|blankett| {
+                let inkomsttagare = blankett.blankettinnehall.inkomsttagare_mut();
+                if !(self.is_protected)(inkomsttagare) {
+                    return None;
+                }
+                let faltkoder = redact(inkomsttagare);
+                (!faltkoder.is_empty()).then_some(Redaction {
+                    blankett: blankett.nummer,
+                    faltkoder,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Clears the address fields and the place of birth, returns the faltkoder of those that were set
+fn redact(inkomsttagare: &mut dyn Inkomsttagare) -> Vec<&'static str> {
+    fn clear<T>(faltkoder: &mut Vec<&'static str>, faltkod: &'static str, field: &mut Option<T>) {
+        if field.take().is_some() {
+            faltkoder.push(faltkod);
+        }
+    }
+
+    let mut faltkoder = vec![];
+    if let Some(field) = inkomsttagare.fodelseort_mut() {
+        clear(&mut faltkoder, "077", field);
+    }
+    if let Some(field) = inkomsttagare.landskod_fodelseort_mut() {
+        clear(&mut faltkoder, "078", field);
+    }
+    clear(&mut faltkoder, "218", inkomsttagare.gatuadress_mut());
+    clear(&mut faltkoder, "219", inkomsttagare.postnummer_mut());
+    clear(&mut faltkoder, "220", inkomsttagare.postort_mut());
+    clear(&mut faltkoder, "221", inkomsttagare.landskod_postort_mut());
+    clear(&mut faltkoder, "228", inkomsttagare.gatuadress2_mut());
+    clear(&mut faltkoder, "230", inkomsttagare.fri_adress_mut());
+    faltkoder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_str, to_string};
+    use std::fs;
+
+    #[test]
+    fn protected_inkomsttagare_are_redacted() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        let inkomsttagare = ku.blanketter[0].blankettinnehall.inkomsttagare_mut();
+        *inkomsttagare.gatuadress_mut() = Some("Storgatan 1".into());
+        *inkomsttagare.postnummer_mut() = Some("62145".into());
+        *inkomsttagare.postort_mut() = Some("Visby".into());
+
+        let unprotected =
+            RedactionPolicy::protected_identities(HashSet::from(["191612299279".to_string()]));
+        assert!(unprotected.apply(&mut ku).is_empty());
+
+        let policy =
+            RedactionPolicy::protected_identities(HashSet::from(["195111232079".to_string()]));
+        assert_eq!(
+            policy.apply(&mut ku),
+            vec![Redaction {
+                blankett: 2300,
+                faltkoder: vec!["218", "219", "220"],
+            }]
+        );
+        let written = to_string(&ku).unwrap();
+        assert!(!written.contains("Storgatan"));
+        assert!(written.contains("195111232079"));
+
+        let everyone = RedactionPolicy::new(|_| true);
+        assert!(everyone.apply(&mut ku).is_empty());
+    }
+}
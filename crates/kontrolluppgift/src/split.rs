@@ -0,0 +1,259 @@
+use crate::error::Error;
+use crate::key::{BlankettKey, InkomsttagareKey};
+use crate::{Avsandare, Blankett, Blankettgemensamt, Kontrolluppgift};
+use quick_xml::Writer;
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// The limits every file created by [split] stays within
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitLimits {
+    pub max_blanketter: usize,
+    /// The size of the written file, in bytes
+    pub max_bytes: usize,
+    /// Keeps every Blankett of an inkomsttagare in the same file
+    pub keep_inkomsttagare_together: bool,
+}
+
+/// Splits the Blanketter into files within the limits, every file gets the same Avsandare and
+/// Blankettgemensamt and its Blanketter are numbered from 1
+///
+/// The files are created as they are iterated, so each can be written with [crate::to_writer]
+/// before the next one is created. A Blankett, or the Blanketter of an inkomsttagare, that does
+/// not fit within the limits on its own is returned as an error, after which no more files are
+/// created.
+pub fn split(kontrolluppgift: Kontrolluppgift<'_>, limits: SplitLimits) -> Split<'_> {
+    let groups = if limits.keep_inkomsttagare_together {
+        by_inkomsttagare(kontrolluppgift.blanketter)
+    } else {
+        kontrolluppgift
+            .blanketter
+            .into_iter()
+            .map(|b| vec![b])
+            .collect()
+    };
+    Split {
+        avsandare: kontrolluppgift.avsandare,
+        blankettgemensamt: kontrolluppgift.blankettgemensamt,
+        limits,
+        groups: groups.into_iter(),
+        carried: None,
+        failed: false,
+    }
+}
+
+/// The files created by [split]
+pub struct Split<'a> {
+    avsandare: Avsandare<'a>,
+    blankettgemensamt: Blankettgemensamt<'a>,
+    limits: SplitLimits,
+    groups: std::vec::IntoIter<Vec<Blankett<'a>>>,
+    /// A group that did not fit in the previous file
+    carried: Option<Vec<Blankett<'a>>>,
+    /// Set after an error, no more files are created
+    failed: bool,
+}
+
+/// Why [split] could not create a file
+#[derive(Debug)]
+pub enum SplitError {
+    /// A Blankett, or the Blanketter of an inkomsttagare, that does not fit within the limits of a
+    /// file on its own
+    TooLarge { blanketter: usize, bytes: usize },
+    /// A Blankett could not be written to measure its size
+    Write(Error),
+}
+
+impl Display for SplitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SplitError::TooLarge { blanketter, bytes } => write!(
+                f,
+                "{} Blanketter of {} bytes do not fit within the limits of a file",
+                blanketter, bytes
+            ),
+            SplitError::Write(e) => write!(f, "the size could not be measured: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SplitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SplitError::Write(e) => Some(e),
+            SplitError::TooLarge { .. } => None,
+        }
+    }
+}
+
+impl From<Error> for SplitError {
+    fn from(e: Error) -> Self {
+        SplitError::Write(e)
+    }
+}
+
+impl<'a> Split<'a> {
+    fn fits(&self, blanketter: usize, bytes: usize) -> bool {
+        blanketter <= self.limits.max_blanketter && bytes <= self.limits.max_bytes
+    }
+
+    fn next_file(&mut self) -> Result<Option<Kontrolluppgift<'a>>, SplitError> {
+        let mut kontrolluppgift = Kontrolluppgift {
+            avsandare: self.avsandare.clone(),
+            blankettgemensamt: self.blankettgemensamt.clone(),
+            blanketter: vec![],
+        };
+        let mut bytes = size_of_file(&kontrolluppgift)?;
+        while let Some(mut group) = self.carried.take().or_else(|| self.groups.next()) {
+            // the nummer is written, so the group is measured as numbered in this file
+            for (b, nummer) in group
+                .iter_mut()
+                .zip(kontrolluppgift.blanketter.len() as i64 + 1..)
+            {
+                b.nummer = nummer;
+            }
+            let size = group.iter().map(size_of).sum::<Result<usize, Error>>()?;
+            let count = kontrolluppgift.blanketter.len() + group.len();
+            if !self.fits(count, bytes + size) {
+                if kontrolluppgift.blanketter.is_empty() {
+                    return Err(SplitError::TooLarge {
+                        blanketter: group.len(),
+                        bytes: size,
+                    });
+                }
+                self.carried = Some(group);
+                break;
+            }
+            bytes += size;
+            kontrolluppgift.blanketter.extend(group);
+        }
+        Ok((!kontrolluppgift.blanketter.is_empty()).then_some(kontrolluppgift))
+    }
+}
+
+impl<'a> Iterator for Split<'a> {
+    type Item = Result<Kontrolluppgift<'a>, SplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let next = self.next_file();
+        self.failed = next.is_err();
+        next.transpose()
+    }
+}
+
+/// Groups the Blanketter by inkomsttagare, in the order each inkomsttagare first occurs
+fn by_inkomsttagare(blanketter: Vec<Blankett<'_>>) -> Vec<Vec<Blankett<'_>>> {
+    let mut index: HashMap<InkomsttagareKey, usize> = HashMap::new();
+    let mut groups: Vec<Vec<Blankett<'_>>> = vec![];
+    for b in blanketter {
+        let inkomsttagare = BlankettKey::from(&b.blankettinnehall).inkomsttagare;
+        match index.get(&inkomsttagare) {
+            Some(&i) => groups[i].push(b),
+            None => {
+                index.insert(inkomsttagare, groups.len());
+                groups.push(vec![b]);
+            }
+        }
+    }
+    groups
+}
+
+fn size_of(blankett: &Blankett) -> Result<usize, Error> {
+    let mut writer = Writer::new(Vec::new());
+    blankett.write(&mut writer)?;
+    Ok(writer.into_inner().len())
+}
+
+fn size_of_file(kontrolluppgift: &Kontrolluppgift) -> Result<usize, Error> {
+    let mut writer = Writer::new(Vec::new());
+    kontrolluppgift.write(&mut writer)?;
+    Ok(writer.into_inner().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::KontrolluppgiftForm;
+    use crate::{from_str, to_string, to_writer};
+    use std::fs;
+
+    #[test]
+    fn files_are_split_within_the_limits() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        for specifikationsnummer in 2..=5 {
+            let mut b = from_str(&xml).unwrap().blanketter.remove(0);
+            *b.blankettinnehall.specifikationsnummer_mut() = specifikationsnummer;
+            if specifikationsnummer % 2 == 0 {
+                *b.blankettinnehall.inkomsttagare_mut().inkomsttagare_mut() =
+                    Some("191612299279".try_into().unwrap());
+            }
+            ku.blanketter.push(b);
+        }
+        let all = to_string(&ku).unwrap();
+        let limits = SplitLimits {
+            max_blanketter: 2,
+            max_bytes: all.len(),
+            keep_inkomsttagare_together: false,
+        };
+
+        let files: Vec<_> = split(from_str(&xml).unwrap(), limits)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].blanketter[0].nummer, 1);
+        // measured as nummer 1 rather than the 2300 it was read with
+        let exact = SplitLimits {
+            max_bytes: size_of_file(&files[0]).unwrap(),
+            ..limits
+        };
+        assert!(split(from_str(&xml).unwrap(), exact).all(|f| f.is_ok()));
+
+        let specifikationsnummer = |files: Vec<Kontrolluppgift>| -> Vec<Vec<i32>> {
+            files
+                .iter()
+                .map(|f| {
+                    let mut written = vec![];
+                    to_writer(&mut written, f).unwrap();
+                    assert!(written.len() <= all.len());
+                    f.blanketter
+                        .iter()
+                        .map(|b| b.blankettinnehall.specifikationsnummer())
+                        .collect()
+                })
+                .collect()
+        };
+        let reread = || from_str(&all).unwrap();
+        let files = split(reread(), limits).map(Result::unwrap).collect();
+        assert_eq!(
+            specifikationsnummer(files),
+            vec![vec![1, 2], vec![3, 4], vec![5]]
+        );
+
+        let together = SplitLimits {
+            max_blanketter: 3,
+            keep_inkomsttagare_together: true,
+            ..limits
+        };
+        let files = split(reread(), together).map(Result::unwrap).collect();
+        assert_eq!(specifikationsnummer(files), vec![vec![1, 3, 5], vec![2, 4]]);
+
+        let too_small = SplitLimits {
+            max_bytes: 100,
+            ..limits
+        };
+        let mut files = split(reread(), too_small);
+        assert!(matches!(
+            files.next(),
+            Some(Err(SplitError::TooLarge { blanketter: 1, .. }))
+        ));
+        assert!(files.next().is_none());
+    }
+}
@@ -0,0 +1,417 @@
+use crate::form::KontrolluppgiftForm;
+use crate::inkomsttagare::validate_inkomsttagare;
+use crate::key::check_duplicates;
+use crate::meta::Fields;
+use crate::value::{FieldAccess, FieldValue, Value};
+use crate::{Kontrolluppgift, KontrolluppgiftType, Writable};
+use regex::Regex;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// A rule from the specification that a kontrolluppgift does not follow
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The nummer of the Blankett the violation was found in, set when validating a whole Kontrolluppgift
+    pub blankett: Option<i64>,
+    /// The faltkoder of the fields involved in the rule
+    pub faltkoder: Vec<&'static str>,
+    pub message: String,
+    /// Where the rule is described in the specification
+    pub rule: Option<Rule>,
+}
+
+/// The faltkod of a form whose description in the specification of the form states the rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub blankett: &'static str,
+    pub faltkod: &'static str,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} faltkod {}", self.blankett, self.faltkod)
+    }
+}
+
+impl Violation {
+    pub fn new(faltkoder: &[&'static str], message: impl Into<String>) -> Self {
+        Violation {
+            blankett: None,
+            faltkoder: faltkoder.to_vec(),
+            message: message.into(),
+            rule: None,
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(nummer) = self.blankett {
+            write!(f, "Blankett {}: ", nummer)?;
+        }
+        if !self.faltkoder.is_empty() {
+            write!(f, "[{}] ", self.faltkoder.join(", "))?;
+        }
+        f.write_str(&self.message)?;
+        if let Some(rule) = self.rule {
+            write!(f, " (see {})", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validation of the rules in the specification that can not be expressed by the types alone
+pub trait Validate {
+    /// Returns every violation found, an empty vec means the value is valid
+    fn validate(&self) -> Vec<Violation>;
+}
+
+/// Checks of the constraints declared with `max_len`, `pattern` and `range` in the `ku` attributes,
+/// generated by the `KontrolluppgiftRead` derive
+pub(crate) trait Constraints {
+    /// Adds a violation for every value that does not follow the constraints of its field,
+    /// nested elements included
+    fn check_constraints(&self, violations: &mut Vec<Violation>);
+}
+
+impl Validate for Kontrolluppgift<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        self.avsandare.check_constraints(&mut violations);
+        self.blankettgemensamt.check_constraints(&mut violations);
+        for b in &self.blanketter {
+            let mut blankett = vec![];
+            b.arendeinformation.check_constraints(&mut blankett);
+            blankett.extend(b.blankettinnehall.validate());
+            violations.extend(blankett.into_iter().map(|mut v| {
+                v.blankett = Some(b.nummer);
+                v
+            }));
+        }
+        check_duplicates(&self.blanketter, &mut violations);
+        violations
+    }
+}
+
+impl Validate for KontrolluppgiftType<'_> {
+    fn validate(&self) -> Vec<Violation> {
+        let mut violations = vec![];
+        self.check_constraints(&mut violations);
+        violations.extend(validate_inkomsttagare(self.inkomsttagare()));
+        violations.extend(match self {
+            KontrolluppgiftType::KU10(ku) => ku.validate(),
+            KontrolluppgiftType::KU13(ku) => ku.validate(),
+            KontrolluppgiftType::KU14(ku) => ku.validate(),
+            KontrolluppgiftType::KU16(ku) => ku.validate(),
+            KontrolluppgiftType::KU17(ku) => ku.validate(),
+            KontrolluppgiftType::KU18(ku) => ku.validate(),
+            KontrolluppgiftType::KU19(ku) => ku.validate(),
+            KontrolluppgiftType::KU20(ku) => ku.validate(),
+            KontrolluppgiftType::KU21(ku) => ku.validate(),
+            KontrolluppgiftType::KU28(ku) => ku.validate(),
+            KontrolluppgiftType::KU31(ku) => ku.validate(),
+            KontrolluppgiftType::KU32(ku) => ku.validate(),
+            _ => vec![],
+        });
+        violations
+    }
+}
+
+fn constraint_violation(
+    element: &str,
+    faltkod: Option<&'static str>,
+    message: String,
+) -> Violation {
+    Violation::new(faltkod.as_slice(), format!("{} {}", element, message))
+}
+
+pub(crate) fn check_max_len<T: Writable>(
+    violations: &mut Vec<Violation>,
+    element: &str,
+    faltkod: Option<&'static str>,
+    value: &T,
+    max_len: usize,
+) {
+    let len = value.get_str().map_or(0, |s| s.chars().count());
+    if len > max_len {
+        violations.push(constraint_violation(
+            element,
+            faltkod,
+            format!("is {} characters, at most {} are allowed", len, max_len),
+        ));
+    }
+}
+
+/// The pattern is matched against the whole value, as in the xsd
+pub(crate) fn check_pattern<T: Writable>(
+    violations: &mut Vec<Violation>,
+    element: &str,
+    faltkod: Option<&'static str>,
+    value: &T,
+    regex: &Regex,
+    pattern: &str,
+) {
+    let text = value.get_str().unwrap_or_default();
+    if !regex.is_match(&text) {
+        violations.push(constraint_violation(
+            element,
+            faltkod,
+            format!("{} does not match the pattern {}", text, pattern),
+        ));
+    }
+}
+
+/// Only applies to numeric fields
+pub(crate) fn check_range<T: FieldValue>(
+    violations: &mut Vec<Violation>,
+    element: &str,
+    faltkod: Option<&'static str>,
+    value: &T,
+    min: i64,
+    max: i64,
+) {
+    let within = match value.to_value() {
+        Value::Int(i) => (min..=max).contains(&i),
+        Value::Andel(a) => (min as f64..=max as f64).contains(&(a as f64)),
+        _ => true,
+    };
+    if !within {
+        violations.push(constraint_violation(
+            element,
+            faltkod,
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+}
+
+/// The number of occurrences of a repeated element, declared with `min` and `max`. They are only
+/// checked when reading and validating, not when writing
+#[allow(dead_code)] // none of the implemented forms contain a repeated element yet
+pub(crate) fn check_occurs(
+    violations: &mut Vec<Violation>,
+    element: &str,
+    faltkod: Option<&'static str>,
+    count: usize,
+    min: usize,
+    max: Option<usize>,
+) {
+    if count < min {
+        violations.push(constraint_violation(
+            element,
+            faltkod,
+            format!("occurs {} times, at least {} are required", count, min),
+        ));
+    } else if let Some(max) = max.filter(|max| count > *max) {
+        violations.push(constraint_violation(
+            element,
+            faltkod,
+            format!("occurs {} times, at most {} are allowed", count, max),
+        ));
+    }
+}
+
+/// Whether a field is present, a checkbox only counts when it is set
+fn is_set<T: FieldAccess>(ku: &T, faltkod: &str) -> bool {
+    !matches!(ku.get_by_code(faltkod), None | Some(Value::Bool(false)))
+}
+
+/// The xml element name of a field, for messages
+fn name<T: Fields>(faltkod: &str) -> &'static str {
+    T::field_by_code(faltkod).map_or("", |f| f.name)
+}
+
+/// A rule of the form, described in the specification of the faltkod
+fn rule<T: Fields>(
+    faltkoder: &[&'static str],
+    faltkod: &'static str,
+    message: String,
+) -> Violation {
+    Violation {
+        rule: Some(Rule {
+            blankett: T::ELEMENT,
+            faltkod,
+        }),
+        ..Violation::new(faltkoder, message)
+    }
+}
+
+/// Adds a violation unless both fields or none of them are present
+pub(crate) fn both_or_neither<T: Fields + FieldAccess>(
+    violations: &mut Vec<Violation>,
+    ku: &T,
+    a: &'static str,
+    b: &'static str,
+) {
+    let (a_present, b_present) = (is_set(ku, a), is_set(ku, b));
+    if a_present != b_present {
+        let (present, missing) = if a_present { (a, b) } else { (b, a) };
+        violations.push(rule::<T>(
+            &[a, b],
+            a,
+            format!(
+                "{} is required when {} is present",
+                name::<T>(missing),
+                name::<T>(present)
+            ),
+        ));
+    }
+}
+
+/// Adds a violation when the first field is present without the second
+pub(crate) fn requires<T: Fields + FieldAccess>(
+    violations: &mut Vec<Violation>,
+    ku: &T,
+    a: &'static str,
+    b: &'static str,
+) {
+    if is_set(ku, a) && !is_set(ku, b) {
+        violations.push(rule::<T>(
+            &[a, b],
+            a,
+            format!(
+                "{} is required when {} is present",
+                name::<T>(b),
+                name::<T>(a)
+            ),
+        ));
+    }
+}
+
+/// Adds a violation when both fields are present
+pub(crate) fn mutually_exclusive<T: Fields + FieldAccess>(
+    violations: &mut Vec<Violation>,
+    ku: &T,
+    a: &'static str,
+    b: &'static str,
+) {
+    if is_set(ku, a) && is_set(ku, b) {
+        violations.push(rule::<T>(
+            &[a, b],
+            a,
+            format!(
+                "{} and {} must not both be present",
+                name::<T>(a),
+                name::<T>(b)
+            ),
+        ));
+    }
+}
+
+/// The faltkoder of every amount that is set on the form
+pub(crate) fn reported_amounts<T: Fields + FieldAccess>(ku: &T) -> Vec<&'static str> {
+    T::FIELDS
+        .iter()
+        .filter(|f| f.amount)
+        .filter_map(|f| f.faltkod)
+        .filter(|faltkod| ku.get_by_code(faltkod).is_some())
+        .collect()
+}
+
+/// Adds a violation when a checkbox, such as Borttag (205), is set on a form that reports amounts
+/// other than the allowed ones
+pub(crate) fn no_amounts_when_set<T: Fields + FieldAccess>(
+    violations: &mut Vec<Violation>,
+    ku: &T,
+    checkbox: &'static str,
+    allowed: &[&'static str],
+) {
+    if !is_set(ku, checkbox) {
+        return;
+    }
+    let mut amounts = reported_amounts(ku);
+    amounts.retain(|a| !allowed.contains(a));
+    if !amounts.is_empty() {
+        let mut faltkoder = vec![checkbox];
+        faltkoder.extend(&amounts);
+        let names: Vec<_> = amounts.iter().map(|a| name::<T>(a)).collect();
+        violations.push(rule::<T>(
+            &faltkoder,
+            checkbox,
+            format!(
+                "{} must not be present when {} is set",
+                names.join(", "),
+                name::<T>(checkbox)
+            ),
+        ));
+    }
+}
+
+/// Adds a violation unless at least one of the fields is present
+pub(crate) fn one_of<T: Fields + FieldAccess>(
+    violations: &mut Vec<Violation>,
+    ku: &T,
+    faltkoder: &[&'static str],
+) {
+    if !faltkoder.iter().any(|f| is_set(ku, f)) {
+        let names: Vec<_> = faltkoder.iter().map(|f| name::<T>(f)).collect();
+        violations.push(rule::<T>(
+            faltkoder,
+            faltkoder[0],
+            format!("one of {} is required", names.join(" or ")),
+        ));
+    }
+}
+
+/// The rules shared by the employment forms KU10, KU13 and KU14
+pub(crate) fn employment_rules<T: Fields + FieldAccess>(violations: &mut Vec<Violation>, ku: &T) {
+    mutually_exclusive(violations, ku, "041", "043");
+    requires(violations, ku, "018", "013");
+    no_amounts_when_set(violations, ku, "205", &[]);
+}
+
+/// The rules of the ship and the days at sea, shared by the seafarer forms KU16 and KU17
+///
+/// The format of Fartygssignal (026) and the at most 366 days of AntalDagarSjoinkomst (027) are
+/// declared on the fields.
+pub(crate) fn sjoinkomst_rules<T: Fields + FieldAccess>(violations: &mut Vec<Violation>, ku: &T) {
+    both_or_neither(violations, ku, "026", "223");
+    requires(violations, ku, "027", "028");
+}
+
+/// The rules of the securities and the depå, shared by the capital income forms KU21, KU31 and KU32
+///
+/// A form with Borttag (205) only identifies the form to remove, so the security is not required.
+/// AvdragenSkatt (001) may be reported when the value is unknown, OkandVarde (599).
+pub(crate) fn vardepapper_rules<T: Fields + FieldAccess>(violations: &mut Vec<Violation>, ku: &T) {
+    requires(violations, ku, "524", "523");
+    if !is_set(ku, "205") {
+        one_of(violations, ku, &["571", "572"]);
+    }
+    no_amounts_when_set(violations, ku, "205", &[]);
+    no_amounts_when_set(violations, ku, "599", &["001"]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use crate::KontrolluppgiftType::KU10;
+    use std::fs;
+
+    #[test]
+    fn declared_constraints_are_validated() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        assert!(ku.validate().is_empty());
+
+        ku.blankettgemensamt.uppgiftslamnare.kontaktperson.namn = "N".repeat(36).into();
+        let KU10(ku10) = &mut ku.blanketter[0].blankettinnehall else {
+            panic!("expected KU10")
+        };
+        ku10.inkomstar = "22".into();
+        ku10.kontant_bruttolon_mm = Some(-1);
+
+        let violations = ku.validate();
+        assert_eq!(
+            violations.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+            vec![
+                "Namn is 36 characters, at most 35 are allowed",
+                "Blankett 2300: [011] KontantBruttolonMm must be between 0 and 999999999",
+                "Blankett 2300: [203] Inkomstar 22 does not match the pattern [0-9]{4}",
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::Error;
+use crate::ku26::Fastighetsbeteckning;
+use crate::ku28::Verksamhetsomrade;
+use crate::{IdentitetsbeteckningForPerson, Isin, KUDate, KUYear, Readable, Writable};
+use std::borrow::Cow;
+
+/// The value of a single field, used when accessing fields by faltkod
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    /// Any value that is written as text, such as names, identity numbers and codes
+    Str(String),
+    Date(KUDate),
+    /// A share, such as AndelAvDepan
+    Andel(f32),
+}
+
+/// Access to the fields of a kontrolluppgift by faltkod, generated by the `KontrolluppgiftRead` derive
+///
+/// Fields of nested elements, such as the inkomsttagare, are reachable from the enclosing element.
+pub trait FieldAccess {
+    /// The value of the field, `None` when the field is not set or there is no field with the faltkod
+    fn get_by_code(&self, faltkod: &str) -> Option<Value>;
+    /// Sets the field, fails when there is no field with the faltkod or the value is of the wrong kind
+    fn set_by_code(&mut self, faltkod: &str, value: Value) -> Result<(), Error>;
+    /// Unsets the field, fails when there is no field with the faltkod or the field is required
+    fn clear_by_code(&mut self, faltkod: &str) -> Result<(), Error>;
+}
+
+/// Conversion between the type of a field and [Value]
+pub(crate) trait FieldValue: Sized {
+    fn to_value(&self) -> Value;
+    fn from_value(value: Value) -> Result<Self, Error>;
+}
+
+pub(crate) fn unknown_faltkod(element: &str, faltkod: &str) -> Error {
+    Error::UnknownFaltkod {
+        element: element.to_string(),
+        faltkod: faltkod.to_string(),
+    }
+}
+
+pub(crate) fn required_faltkod(element: &str, faltkod: &str) -> Error {
+    Error::RequiredField {
+        element: element.to_string(),
+        faltkod: faltkod.to_string(),
+    }
+}
+
+fn unexpected_value(expected: &str, value: &Value) -> Error {
+    Error::WrongValueKind {
+        expected: expected.to_string(),
+        found: format!("{:?}", value),
+    }
+}
+
+/// For types that are written as text
+pub(crate) fn text_to_value<T: Writable>(v: &T) -> Value {
+    Value::Str(v.get_str().unwrap_or_default())
+}
+
+/// For types that are read from text
+pub(crate) fn text_from_value<'a, T: Readable<'a, 'a> + 'a>(value: Value) -> Result<T, Error> {
+    match value {
+        Value::Str(s) => T::get_str(Cow::Owned(s)),
+        v => Err(unexpected_value("text", &v)),
+    }
+}
+
+impl FieldValue for i32 {
+    fn to_value(&self) -> Value {
+        Value::Int(*self as i64)
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Int(i) => i
+                .try_into()
+                .map_err(|_| unexpected_value("i32", &Value::Int(i))),
+            Value::Str(s) => Readable::get_str(Cow::Owned(s)),
+            v => Err(unexpected_value("int", &v)),
+        }
+    }
+}
+
+impl FieldValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            Value::Str(s) => Readable::get_str(Cow::Owned(s)),
+            v => Err(unexpected_value("bool", &v)),
+        }
+    }
+}
+
+impl FieldValue for f32 {
+    fn to_value(&self) -> Value {
+        Value::Andel(*self)
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Andel(a) => Ok(a),
+            Value::Str(s) => Readable::get_str(Cow::Owned(s)),
+            v => Err(unexpected_value("andel", &v)),
+        }
+    }
+}
+
+impl FieldValue for KUDate {
+    fn to_value(&self) -> Value {
+        Value::Date(self.clone())
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Date(d) => Ok(d),
+            Value::Str(s) => Readable::get_str(Cow::Owned(s)),
+            v => Err(unexpected_value("date", &v)),
+        }
+    }
+}
+
+impl FieldValue for KUYear {
+    fn to_value(&self) -> Value {
+        Value::Int(self.value() as i64)
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Int(i) => i
+                .try_into()
+                .ok()
+                .and_then(|i| KUYear::new(i).ok())
+                .ok_or_else(|| unexpected_value("year", &Value::Int(i))),
+            Value::Str(s) => Readable::get_str(Cow::Owned(s)),
+            v => Err(unexpected_value("year", &v)),
+        }
+    }
+}
+
+impl<'a> FieldValue for Cow<'a, str> {
+    fn to_value(&self) -> Value {
+        text_to_value(self)
+    }
+
+    fn from_value(value: Value) -> Result<Self, Error> {
+        text_from_value(value)
+    }
+}
+
+macro_rules! text_field_value {
+    ($($ty:ident),*) => {
+        $(
+            impl<'a> FieldValue for $ty<'a> {
+                fn to_value(&self) -> Value {
+                    text_to_value(self)
+                }
+
+                fn from_value(value: Value) -> Result<Self, Error> {
+                    text_from_value(value)
+                }
+            }
+        )*
+    };
+}
+
+text_field_value!(
+    IdentitetsbeteckningForPerson,
+    Isin,
+    Fastighetsbeteckning,
+    Verksamhetsomrade
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use std::fs;
+
+    #[test]
+    fn fields_are_accessed_by_faltkod() {
+        let xml = fs::read_to_string(
+            "./EXEMPELFIL KONTROLLUPPGIFT FÖR ARBETSGIVARE MED SOCIALAVGIFTSAVTAL (KU10)_2022.xml",
+        )
+        .unwrap();
+        let mut ku = from_str(&xml).unwrap();
+        let form = &mut ku.blanketter[0].blankettinnehall;
+
+        assert_eq!(form.get_by_code("011"), Some(Value::Int(335000)));
+        assert_eq!(form.get_by_code("093"), Some(Value::Bool(true)));
+        assert_eq!(form.get_by_code("012"), None);
+        assert_eq!(
+            form.get_by_code("215"),
+            Some(Value::Str("195111232079".into()))
+        );
+
+        form.set_by_code("012", Value::Int(1200)).unwrap();
+        form.set_by_code("076", Value::Str("FI".into())).unwrap();
+        form.clear_by_code("093").unwrap();
+        assert_eq!(form.get_by_code("012"), Some(Value::Int(1200)));
+        assert_eq!(form.get_by_code("076"), Some(Value::Str("FI".into())));
+        assert_eq!(form.get_by_code("093"), None);
+
+        assert!(matches!(
+            form.set_by_code("011", Value::Bool(true)),
+            Err(Error::WrongValueKind { expected, .. }) if expected == "int"
+        ));
+        assert!(matches!(
+            form.set_by_code("011", Value::Int(i64::MAX)),
+            Err(Error::WrongValueKind { expected, .. }) if expected == "i32"
+        ));
+        assert!(form.set_by_code("076", Value::Str("XX".into())).is_err());
+        assert!(matches!(
+            form.set_by_code("999", Value::Int(1)),
+            Err(Error::UnknownFaltkod { element, faltkod }) if element == "KU10" && faltkod == "999"
+        ));
+        assert!(matches!(
+            form.clear_by_code("203"),
+            Err(Error::RequiredField { faltkod, .. }) if faltkod == "203"
+        ));
+        assert_eq!(
+            form.clear_by_code("999").unwrap_err().to_string(),
+            "KU10 has no field with faltkod 999"
+        );
+    }
+}
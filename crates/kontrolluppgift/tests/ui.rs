@@ -0,0 +1,5 @@
+#[test]
+fn duplicates_are_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
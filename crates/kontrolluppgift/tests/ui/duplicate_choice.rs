@@ -0,0 +1,11 @@
+use kontrolluppgift_macros::KontrolluppgiftChoice;
+
+#[derive(KontrolluppgiftChoice)]
+enum Betalning {
+    #[ku(name(b"Kontant"), code("003"))]
+    Kontant(i32),
+    #[ku(name(b"Kontant"), code("004"))]
+    Konto(i32),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use kontrolluppgift_macros::KontrolluppgiftWrite;
+use std::borrow::Cow;
+
+#[derive(KontrolluppgiftWrite)]
+#[ku(name("InkomsttagareKU31"))]
+struct InkomsttagareKU31<'a> {
+    #[ku(name(b"Fodelseort"), code("077"), seq(1))]
+    fodelseort: Option<Cow<'a, str>>,
+    #[ku(name(b"Fodelseort"), code("078"), seq(2))]
+    landskod_fodelseort: Option<Cow<'a, str>>,
+}
+
+fn main() {}
@@ -1,12 +1,20 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
+use std::collections::HashSet;
 use syn::__private::Span;
+use syn::parse::ParseStream;
 use syn::spanned::Spanned;
 use syn::{
     parenthesized, parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Error,
-    Field, Fields, FieldsNamed, Ident, LitBool, LitByteStr, LitStr, Type, Variant,
+    Field, Fields, FieldsNamed, GenericArgument, Ident, LitBool, LitByteStr, LitInt, LitStr,
+    PathArguments, Token, Type, Variant,
 };
 
+/// Reads the struct from its xml element, each field is described by a `#[ku(...)]` attribute
+///
+/// A faltkod or element name used by two fields is a compile error, see the ui tests of the
+/// kontrolluppgift crate.
 #[proc_macro_derive(KontrolluppgiftRead, attributes(ku))]
 pub fn read_macro(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
@@ -21,97 +29,373 @@ pub fn read_macro(input: TokenStream) -> TokenStream {
 
     let fields = get_fields(ast.data);
 
-    let field_data: Result<
-        Vec<(LitByteStr, Ident, Ident, Type, bool, Option<LitStr>, bool)>,
-        Error,
-    > = fields
-        .named
-        .into_iter()
-        .enumerate()
-        .map(|(index, field)| {
-            let ret = parse_field_attribute_data(&field)?;
+    let field_data = parse_fields(fields);
 
-            let ident = field
-                .ident
-                .clone()
-                .ok_or_else(|| Error::new(field.span(), "Expected a named identifier"))?;
-            Ok((
-                ret.name,
-                Ident::new(&format!("g_field_{}", index), ident.span()),
-                ident,
-                field.ty,
-                ret.required,
-                ret.code,
-                ret.is_inner_type,
-            ))
-        })
-        .collect();
+    // nothing is generated for a struct with duplicates, so the duplicate is the only error shown
+    if let Ok(field) = &field_data {
+        if let Err(e) = check_duplicates(elements(field)) {
+            return e.to_compile_error().into();
+        }
+    }
 
     match field_data {
         Err(err) => err.to_compile_error().into(),
         Ok(field) => {
-            let struct_assignments: Result<Vec<_>, Error> = field.iter().map(|(name, temp, og, _, req, _, _)| {
-                if *req {
-                    let str = String::from_utf8(name.value())
-                        .map_err(|_| Error::new(og.span(), "name attribute was not valid utf-8 ku(name=\"...\")"))?;
-                    let element = str_name.value();
-                    Ok(quote! {
+            let element = str_name.value();
+            let unique_choices = choices_are_unique(&name, &field);
+            let struct_assignments: Vec<_> = field.iter().map(|f| {
+                let og = &f.ident;
+                let temp = &f.temp;
+                let str = &f.name_str;
+                if f.repeated {
+                    let min = f.min_occurs();
+                    let min_check = (min > 0).then(|| quote! {
+                        if #temp.len() < #min {
+                            return Err(crate::Error::MissingElement { missing: #str.to_string(), reading: #element.to_string() });
+                        }
+                    });
+                    let max_check = f.attr.max.as_ref().map(|max| quote! {
+                        if #temp.len() > #max {
+                            return Err(crate::Error::TooManyElements { element: #str.to_string(), reading: #element.to_string(), max: #max });
+                        }
+                    });
+                    quote! {
+                        #og: {
+                            #min_check
+                            #max_check
+                            #temp
+                        },
+                    }
+                } else if f.attr.required {
+                    quote! {
                         #og: #temp.ok_or_else(|| crate::Error::MissingElement { missing: #str.to_string(), reading: #element.to_string() })?,
-                    })
+                    }
                 } else {
-                    Ok(quote! {
+                    quote! {
                         #og: #temp,
-                    })
+                    }
                 }
             }).collect();
 
-            let struct_assignments = match struct_assignments {
-                Ok(res) => res,
-                Err(e) => return e.to_compile_error().into(),
-            };
-
             let match_branches: Vec<_> = field
                 .iter()
-                .map(|(name, temp, _, typ, _, code, is_inner)| {
-                    if *is_inner {
-                        let Type::Path(type_path) = typ.clone() else { panic!("expected path") };
-                        let type_name = &type_path
-                            .path
-                            .segments
-                            .last()
-                            .expect("Path should always be at least one element")
-                            .ident;
-                        quote! {
-                            #name => { #temp = Some(#type_name::read(reader, &element)?) },
+                .enumerate()
+                .filter(|(_, f)| !f.attr.choice)
+                .map(|(index, f)| {
+                    let name = &f.attr.name;
+                    let temp = &f.temp;
+                    let repeated = f.repeated;
+                    let element_type = f.element_type();
+                    let read = if f.attr.is_inner_type {
+                        quote! { <#element_type as crate::KontrolluppgiftRead<'a>>::read(reader, &element, strict)? }
+                    } else {
+                        let target = if repeated { quote!(&mut value) } else { quote!(&mut #temp) };
+                        let read = match &f.attr.code {
+                            Some(code) => quote! { reader.read_node_into_with_code(element, #code, #target)? },
+                            None => quote! { reader.read_node_into(element, #target)? },
+                        };
+                        if repeated {
+                            quote! {{
+                                let mut value: Option<#element_type> = None;
+                                #read;
+                                value
+                            }}
+                        } else {
+                            read
                         }
-                    } else if code.is_some() {
+                    };
+                    let store = match (repeated, f.attr.is_inner_type) {
+                        (true, true) => quote! { #temp.push(#read) },
+                        (true, false) => quote! { #temp.extend(#read) },
+                        (false, true) => quote! { #temp = Some(#read) },
+                        (false, false) => read,
+                    };
+                    quote! {
+                        #name => {
+                            crate::check_sequence(strict, &mut g_position, #index, #repeated, &element, #element)?;
+                            #store
+                        },
+                    }
+                })
+                .collect();
+
+            let choice_branches: Vec<_> = field
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.attr.choice)
+                .map(|(index, f)| {
+                    let temp = &f.temp;
+                    let element_type = f.element_type();
+                    quote! {
+                        if <#element_type as crate::KontrolluppgiftChoice<'a>>::is_choice(element.local_name().as_ref()) {
+                            crate::check_sequence(strict, &mut g_position, #index, false, &element, #element)?;
+                            #temp = Some(<#element_type as crate::KontrolluppgiftChoice<'a>>::read_choice(reader, element, strict)?);
+                            continue;
+                        }
+                    }
+                })
+                .collect();
+
+            let variable_definitions: Vec<_> = field
+                .iter()
+                .map(|f| {
+                    let temp = &f.temp;
+                    if f.repeated {
                         quote! {
-                            #name => reader.read_node_into_with_code(element, #code, &mut #temp)?,
+                            let mut #temp = Vec::new();
                         }
                     } else {
                         quote! {
-                            #name => reader.read_node_into(element, &mut #temp)?,
+                            let mut #temp = None;
                         }
                     }
                 })
                 .collect();
 
-            let variable_definitions: Vec<_> = field
+            let field_metas: Result<Vec<_>, Error> = field
                 .iter()
-                .map(|(_, temp, _, _, _, _, _)| {
+                .map(|f| {
+                    let name = &f.name_str;
+                    let field_name = f.ident.to_string();
+                    let code = match &f.attr.code {
+                        Some(code) => quote! { Some(#code) },
+                        None => quote! { None },
+                    };
+                    let type_str = type_to_string(value_type(&f.ty));
+                    let required = f.attr.required;
+                    let amount = f.attr.amount;
+                    let min_occurs = f.min_occurs();
+                    let max_occurs = match (&f.attr.max, f.repeated) {
+                        (Some(max), _) => quote! { Some(#max) },
+                        (None, true) => quote! { None },
+                        (None, false) => quote! { Some(1) },
+                    };
+                    let children = if f.attr.is_inner_type || f.attr.choice {
+                        let type_name = last_type_ident(f.element_type())?;
+                        quote! { Some(<#type_name<'static> as crate::meta::Fields>::FIELDS) }
+                    } else {
+                        quote! { None }
+                    };
+                    Ok(quote! {
+                        crate::meta::FieldMeta {
+                            name: #name,
+                            field: #field_name,
+                            faltkod: #code,
+                            ty: #type_str,
+                            required: #required,
+                            amount: #amount,
+                            min_occurs: #min_occurs,
+                            max_occurs: #max_occurs,
+                            children: #children,
+                        },
+                    })
+                })
+                .collect();
+
+            let field_metas = match field_metas {
+                Ok(res) => res,
+                Err(e) => return e.to_compile_error().into(),
+            };
+
+            // repeated fields and choices have no single value to access by faltkod
+            let single = |f: &&FieldData| !f.repeated && !f.attr.choice;
+            let coded: Vec<_> = field
+                .iter()
+                .filter(single)
+                .filter(|f| f.attr.code.is_some() && !f.attr.is_inner_type)
+                .collect();
+            let codes: Vec<_> = coded
+                .iter()
+                .map(|f| f.attr.code.as_ref().unwrap())
+                .collect();
+            let get_values: Vec<_> = coded
+                .iter()
+                .map(|f| {
+                    let og = &f.ident;
+                    if f.attr.required {
+                        quote! { Some(crate::value::FieldValue::to_value(&self.#og)) }
+                    } else {
+                        quote! { self.#og.as_ref().map(crate::value::FieldValue::to_value) }
+                    }
+                })
+                .collect();
+            let set_values: Vec<_> = coded
+                .iter()
+                .map(|f| {
+                    let og = &f.ident;
+                    if f.attr.required {
+                        quote! { self.#og = crate::value::FieldValue::from_value(value)? }
+                    } else {
+                        quote! { self.#og = Some(crate::value::FieldValue::from_value(value)?) }
+                    }
+                })
+                .collect();
+            let clear_values: Vec<_> = coded
+                .iter()
+                .map(|f| {
+                    let og = &f.ident;
+                    let code = &f.attr.code;
+                    if f.attr.required {
+                        quote! { return Err(crate::value::required_faltkod(#str_name, #code)) }
+                    } else {
+                        quote! { self.#og = None }
+                    }
+                })
+                .collect();
+
+            let inner: Vec<_> = field
+                .iter()
+                .filter(single)
+                .filter(|f| f.attr.is_inner_type)
+                .collect();
+            let inner_get: Vec<_> = inner
+                .iter()
+                .map(|f| {
+                    let og = &f.ident;
+                    if f.attr.required {
+                        quote! { .or_else(|| crate::value::FieldAccess::get_by_code(&self.#og, faltkod)) }
+                    } else {
+                        quote! { .or_else(|| self.#og.as_ref().and_then(|v| crate::value::FieldAccess::get_by_code(v, faltkod))) }
+                    }
+                })
+                .collect();
+            let inner_access: Result<Vec<_>, Error> = inner
+                .iter()
+                .map(|f| {
+                    let og = &f.ident;
+                    let ty = last_type_ident(f.element_type())?;
+                    let has_code = quote! { <#ty<'static> as crate::meta::Fields>::field_by_code(faltkod).is_some() };
+                    Ok(if f.attr.required {
+                        (
+                            quote! { _ if #has_code => return crate::value::FieldAccess::set_by_code(&mut self.#og, faltkod, value), },
+                            quote! { _ if #has_code => return crate::value::FieldAccess::clear_by_code(&mut self.#og, faltkod), },
+                        )
+                    } else {
+                        (
+                            quote! { _ if #has_code => return match &mut self.#og {
+                                Some(v) => crate::value::FieldAccess::set_by_code(v, faltkod, value),
+                                None => Err(crate::Error::MissingElement { missing: <#ty<'static> as crate::meta::Fields>::ELEMENT.to_string(), reading: #element.to_string() }),
+                            }, },
+                            quote! { _ if #has_code => return match &mut self.#og {
+                                Some(v) => crate::value::FieldAccess::clear_by_code(v, faltkod),
+                                None => Ok(()),
+                            }, },
+                        )
+                    })
+                })
+                .collect();
+            let (inner_set, inner_clear): (Vec<_>, Vec<_>) = match inner_access {
+                Ok(res) => res.into_iter().unzip(),
+                Err(e) => return e.to_compile_error().into(),
+            };
+
+            let constraints: Vec<_> = field
+                .iter()
+                .filter(|f| f.repeated || f.attr.is_inner_type || f.attr.choice || f.attr.has_constraints())
+                .map(|f| {
+                    let og = &f.ident;
+                    let occurs = f.repeated.then(|| {
+                        let name_str = &f.name_str;
+                        let faltkod = match &f.attr.code {
+                            Some(code) => quote! { Some(#code) },
+                            None => quote! { None },
+                        };
+                        let min = f.min_occurs();
+                        let max = match &f.attr.max {
+                            Some(max) => quote! { Some(#max) },
+                            None => quote! { None },
+                        };
+                        quote! {
+                            crate::validation::check_occurs(violations, #name_str, #faltkod, self.#og.len(), #min, #max);
+                        }
+                    });
+                    let values = if f.repeated || !f.attr.required {
+                        quote! { self.#og.iter() }
+                    } else {
+                        quote! { std::iter::once(&self.#og) }
+                    };
+                    let checks = if f.attr.is_inner_type || f.attr.choice {
+                        quote! { crate::validation::Constraints::check_constraints(v, violations); }
+                    } else {
+                        constraint_checks(&f.attr, &f.name_str)
+                    };
                     quote! {
-                        let mut #temp = None;
+                        #occurs
+                        for v in #values {
+                            #checks
+                        }
                     }
                 })
                 .collect();
 
             // Build the output, possibly using quasi-quotation
             let expanded = quote! {
+                impl<'a> crate::validation::Constraints for #name<'a> {
+                    #[allow(unused_variables)]
+                    fn check_constraints(&self, violations: &mut Vec<crate::validation::Violation>) {
+                        #(
+                            #constraints
+                        )*
+                    }
+                }
+
+                impl<'a> crate::value::FieldAccess for #name<'a> {
+                    fn get_by_code(&self, faltkod: &str) -> Option<crate::value::Value> {
+                        match faltkod {
+                            #(
+                                #codes => #get_values,
+                            )*
+                            _ => None #( #inner_get )*
+                        }
+                    }
+
+                    #[allow(unused_variables)]
+                    fn set_by_code(&mut self, faltkod: &str, value: crate::value::Value) -> Result<(), crate::error::Error> {
+                        match faltkod {
+                            #(
+                                #codes => #set_values,
+                            )*
+                            #(
+                                #inner_set
+                            )*
+                            _ => return Err(crate::value::unknown_faltkod(#str_name, faltkod)),
+                        }
+                        #[allow(unreachable_code)]
+                        Ok(())
+                    }
+
+                    fn clear_by_code(&mut self, faltkod: &str) -> Result<(), crate::error::Error> {
+                        match faltkod {
+                            #(
+                                #codes => #clear_values,
+                            )*
+                            #(
+                                #inner_clear
+                            )*
+                            _ => return Err(crate::value::unknown_faltkod(#str_name, faltkod)),
+                        }
+                        #[allow(unreachable_code)]
+                        Ok(())
+                    }
+                }
+
+
+                impl<'a> crate::meta::Fields for #name<'a> {
+                    const ELEMENT: &'static str = #str_name;
+                    const FIELDS: &'static [crate::meta::FieldMeta] = &[
+                        #(
+                            #field_metas
+                        )*
+                    ];
+                }
+
+
                 impl<'a> crate::KontrolluppgiftRead<'a> for #name<'a> {
-                    fn read(reader: &mut quick_xml::NsReader<&'a [u8]>, tag: &quick_xml::events::BytesStart) -> Result<Self, crate::error::Error> {
+                    fn read(reader: &mut quick_xml::NsReader<&'a [u8]>, tag: &quick_xml::events::BytesStart, strict: bool) -> Result<Self, crate::error::Error> {
                         #(
                             #variable_definitions
                         )*
+                        let mut g_position = None;
                         loop {
                             use crate::Reader;
                             match reader.read_event()? {
@@ -119,7 +403,12 @@ pub fn read_macro(input: TokenStream) -> TokenStream {
                                     #(
                                         #match_branches
                                     )*
-                                    &_ => crate::unexpected_element(&element)?
+                                    &_ => {
+                                        #(
+                                            #choice_branches
+                                        )*
+                                        crate::unexpected_element(&element)?
+                                    }
                                 }
                                 quick_xml::events::Event::End(element) => {
                                     if element.name() == tag.name() {
@@ -136,12 +425,16 @@ pub fn read_macro(input: TokenStream) -> TokenStream {
                         }
                     }
                 }
+
+                #unique_choices
             };
             TokenStream::from(expanded)
         }
     }
 }
 
+/// Writes the struct as its xml element, in the order of the xsd sequence. The `min` and `max` of
+/// repeated elements are not checked, validation reports them
 #[proc_macro_derive(KontrolluppgiftWrite, attributes(ku))]
 pub fn write_macro(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
@@ -157,44 +450,60 @@ pub fn write_macro(input: TokenStream) -> TokenStream {
 
     let fields = get_fields(ast.data);
 
-    let field_data: Result<Vec<(String, Ident, Option<LitStr>, bool)>, Error> = fields
-        .named
-        .into_iter()
-        .map(|field| {
-            let ret = parse_field_attribute_data(&field)?;
-
-            let ident = field
-                .ident
-                .clone()
-                .ok_or_else(|| Error::new(field.span(), "Expected a named field"))?;
-
-            let name_str = String::from_utf8(ret.name.value())
-                .map_err(|_| Error::new(field.span(), "the name is required to be valid utf-8"))?;
+    let field_data = parse_fields(fields);
 
-            Ok((name_str, ident, ret.code, ret.is_inner_type))
-        })
-        .collect();
+    if let Ok(field) = &field_data {
+        if let Err(e) = check_duplicates(elements(field)) {
+            return e.to_compile_error().into();
+        }
+    }
 
     match field_data {
         Err(err) => err.to_compile_error().into(),
         Ok(field) => {
             let write_operations: Vec<_> = field
                 .iter()
-                .map(|(name, og, code, is_inner_type)| match code {
-                    None => {
-                        if *is_inner_type {
+                .map(|f| {
+                    let og = &f.ident;
+                    let name = &f.name_str;
+                    if f.attr.is_inner_type || f.attr.choice {
+                        if f.repeated {
+                            quote! {
+                                for v in &self.#og {
+                                    v.write(w)?;
+                                }
+                            }
+                        } else if f.attr.required {
                             quote! {
                                 self.#og.write(w)?;
                             }
                         } else {
                             quote! {
-                                w.write_node(#name, &self.#og)?;
+                                if let Some(v) = &self.#og {
+                                    v.write(w)?;
+                                }
                             }
                         }
-                    }
-                    Some(code) => {
+                    } else if f.repeated {
+                        let write = match &f.attr.code {
+                            Some(code) => {
+                                quote! { w.write_node_with_code(#name, #code, Some(v))?; }
+                            }
+                            None => quote! { w.write_node(#name, Some(v))?; },
+                        };
                         quote! {
-                            w.write_node_with_code(#name, #code, &self.#og)?;
+                            for v in &self.#og {
+                                #write
+                            }
+                        }
+                    } else {
+                        match &f.attr.code {
+                            Some(code) => quote! {
+                                w.write_node_with_code(#name, #code, &self.#og)?;
+                            },
+                            None => quote! {
+                                w.write_node(#name, &self.#og)?;
+                            },
                         }
                     }
                 })
@@ -214,12 +523,308 @@ pub fn write_macro(input: TokenStream) -> TokenStream {
                         Ok(())
                     }
                 }
+
             };
             TokenStream::from(expanded)
         }
     }
 }
 
+/// One of the elements of an xs:choice, written as `#[ku(name(b"..."), code("..."))]` or
+/// `#[ku(name(b"..."), inner_ty(true))]` on each variant, which hold the value in a single field
+#[proc_macro_derive(KontrolluppgiftChoice, attributes(ku))]
+pub fn choice_macro(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let name = ast.ident;
+    let str_name = name.to_string();
+    let variants = get_enum_fields(ast.data);
+
+    let alternatives: Result<Vec<_>, Error> = variants
+        .iter()
+        .map(|variant| {
+            let attr = parse_attribute_data(&variant.attrs)?;
+            if attr.is_inner_type && attr.has_constraints() {
+                return Err(Error::new(
+                    variant.span(),
+                    "constraints are declared on the fields of the inner type",
+                ));
+            }
+            let ty = match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    fields.unnamed[0].ty.clone()
+                }
+                _ => {
+                    return Err(Error::new(
+                        variant.span(),
+                        "choice variants must hold a single value",
+                    ))
+                }
+            };
+            let element = attr
+                .name
+                .clone()
+                .ok_or_else(|| Error::new(variant.span(), "must have a name ku(name(b\"...\"))"))?;
+            let name_str = String::from_utf8(element.value()).map_err(|_| {
+                Error::new(variant.span(), "the name is required to be valid utf-8")
+            })?;
+            Ok((variant.ident.clone(), element, name_str, attr, ty))
+        })
+        .collect();
+    let alternatives = match alternatives {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let duplicates =
+        check_duplicates(alternatives.iter().map(|(_, element, name_str, attr, _)| {
+            (name_str.as_str(), element.span(), attr.code.as_ref())
+        }));
+    if let Err(e) = duplicates {
+        return e.to_compile_error().into();
+    }
+
+    let names: Vec<_> = alternatives.iter().map(|a| &a.1).collect();
+    let constraints: Vec<_> = alternatives
+        .iter()
+        .map(|(ident, _, name_str, attr, _)| {
+            let checks = if attr.is_inner_type {
+                quote! { crate::validation::Constraints::check_constraints(v, violations) }
+            } else {
+                constraint_checks(attr, name_str)
+            };
+            quote! {
+                #name::#ident(v) => {
+                    #checks
+                }
+            }
+        })
+        .collect();
+    let read_branches: Vec<_> = alternatives
+        .iter()
+        .map(|(ident, element, name_str, attr, ty)| {
+            if attr.is_inner_type {
+                quote! {
+                    #element => Ok(#name::#ident(<#ty as crate::KontrolluppgiftRead<'a>>::read(reader, &element, strict)?)),
+                }
+            } else {
+                let read = match &attr.code {
+                    Some(code) => quote! { reader.read_node_into_with_code(element, #code, &mut value)? },
+                    None => quote! { reader.read_node_into(element, &mut value)? },
+                };
+                quote! {
+                    #element => {
+                        let mut value = None;
+                        #read;
+                        value.map(#name::#ident).ok_or_else(|| crate::Error::MissingElement { missing: #name_str.to_string(), reading: #str_name.to_string() })
+                    }
+                }
+            }
+        })
+        .collect();
+    let write_branches: Vec<_> = alternatives
+        .iter()
+        .map(|(ident, _, name_str, attr, _)| {
+            if attr.is_inner_type {
+                quote! { #name::#ident(v) => v.write(w)?, }
+            } else {
+                match &attr.code {
+                    Some(code) => quote! { #name::#ident(v) => w.write_node_with_code(#name_str, #code, Some(v))?, },
+                    None => quote! { #name::#ident(v) => w.write_node(#name_str, Some(v))?, },
+                }
+            }
+        })
+        .collect();
+    let field_metas: Result<Vec<_>, Error> = alternatives
+        .iter()
+        .map(|(ident, _, name_str, attr, ty)| {
+            let field_name = ident.to_string();
+            let code = match &attr.code {
+                Some(code) => quote! { Some(#code) },
+                None => quote! { None },
+            };
+            let type_str = type_to_string(ty);
+            let amount = attr.amount;
+            let children = if attr.is_inner_type {
+                let type_name = last_type_ident(ty)?;
+                quote! { Some(<#type_name<'static> as crate::meta::Fields>::FIELDS) }
+            } else {
+                quote! { None }
+            };
+            Ok(quote! {
+                crate::meta::FieldMeta {
+                    name: #name_str,
+                    field: #field_name,
+                    faltkod: #code,
+                    ty: #type_str,
+                    required: false,
+                    amount: #amount,
+                    min_occurs: 0,
+                    max_occurs: Some(1),
+                    children: #children,
+                },
+            })
+        })
+        .collect();
+    let field_metas = match field_metas {
+        Ok(res) => res,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let expanded = quote! {
+        impl<'a> crate::validation::Constraints for #name<'a> {
+            #[allow(unused_variables)]
+            fn check_constraints(&self, violations: &mut Vec<crate::validation::Violation>) {
+                match self {
+                    #(
+                        #constraints
+                    )*
+                }
+            }
+        }
+
+        impl<'a> crate::KontrolluppgiftChoice<'a> for #name<'a> {
+            fn is_choice(element: &[u8]) -> bool {
+                matches!(element, #(#names)|*)
+            }
+
+            fn read_choice(reader: &mut quick_xml::NsReader<&'a [u8]>, element: quick_xml::events::BytesStart, strict: bool) -> Result<Self, crate::error::Error> {
+                use crate::Reader;
+                match element.local_name().as_ref() {
+                    #(
+                        #read_branches
+                    )*
+                    &_ => crate::unexpected_element(&element),
+                }
+            }
+        }
+
+        impl<'a> crate::KontrolluppgiftWrite for #name<'a> {
+            fn write<W>(&self, w: &mut crate::Writer<W>) -> Result<(), quick_xml::Error> where W: std::io::Write {
+                use crate::Write;
+                match self {
+                    #(
+                        #write_branches
+                    )*
+                }
+                Ok(())
+            }
+        }
+
+        impl<'a> crate::meta::Fields for #name<'a> {
+            const ELEMENT: &'static str = #str_name;
+            const FIELDS: &'static [crate::meta::FieldMeta] = &[
+                #(
+                    #field_metas
+                )*
+            ];
+        }
+    };
+    TokenStream::from(expanded)
+}
+
+/// Fails on the second element using a name or faltkod already used in the struct or enum
+fn check_duplicates<'f>(
+    elements: impl IntoIterator<Item = (&'f str, Span, Option<&'f LitStr>)>,
+) -> Result<(), Error> {
+    let mut names = HashSet::new();
+    let mut codes = HashSet::new();
+    let mut errors: Option<Error> = None;
+    let mut push = |error: Error| match &mut errors {
+        Some(errors) => errors.combine(error),
+        None => errors = Some(error),
+    };
+    for (name, span, code) in elements {
+        if !names.insert(name) {
+            push(Error::new(span, format!("duplicate element name {}", name)));
+        }
+        if let Some(code) = code {
+            if !codes.insert(code.value()) {
+                push(Error::new(
+                    code.span(),
+                    format!("duplicate faltkod {}", code.value()),
+                ));
+            }
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// The element names and faltkoder of the fields, the elements of a choice are named by its
+/// variants and checked against the struct by [choices_are_unique]
+fn elements(fields: &[FieldData]) -> impl Iterator<Item = (&str, Span, Option<&LitStr>)> {
+    fields.iter().filter(|f| !f.attr.choice).map(|f| {
+        let span = f.attr.name.as_ref().map_or(f.ident.span(), |n| n.span());
+        (f.name_str.as_str(), span, f.attr.code.as_ref())
+    })
+}
+
+/// A compile time check that the variants of the choices use no element name or faltkod of the
+/// struct or of another choice, the variants are only known once the enums are compiled
+fn choices_are_unique(name: &Ident, fields: &[FieldData]) -> Option<TokenStream2> {
+    let choices: Vec<_> = fields
+        .iter()
+        .filter(|f| f.attr.choice)
+        .map(|f| last_type_ident(f.element_type()))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if choices.is_empty() {
+        return None;
+    }
+    let names: Vec<_> = elements(fields).map(|(name, _, _)| name).collect();
+    let codes: Vec<_> = elements(fields).filter_map(|(_, _, code)| code).collect();
+    let message = format!("a choice of {} repeats an element name or faltkod", name);
+    Some(quote! {
+        const _: () = assert!(
+            crate::meta::choices_are_unique(
+                &[#(#names),*],
+                &[#(#codes),*],
+                &[#(<#choices<'static> as crate::meta::Fields>::FIELDS),*],
+            ),
+            #message
+        );
+    })
+}
+
+/// The type of the value held by a field, `Option<T>` becomes `T`
+fn value_type(ty: &Type) -> &Type {
+    generic_argument(ty, "Option").unwrap_or(ty)
+}
+
+/// The `T` of `wrapper<T>`
+fn generic_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == wrapper {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                        return Some(inner);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn type_to_string(ty: &Type) -> String {
+    quote!(#ty).to_string().replace(' ', "").replace(',', ", ")
+}
+
+fn last_type_ident(ty: &Type) -> Result<&Ident, Error> {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| &segment.ident)
+            .ok_or_else(|| Error::new(ty.span(), "expected a type path")),
+        _ => Err(Error::new(ty.span(), "expected a type path")),
+    }
+}
+
 fn get_fields(data: Data) -> FieldsNamed {
     match data {
         Data::Enum(_) | Data::Union(_) => {
@@ -270,13 +875,20 @@ fn parse_name_input(span: Span, attrs: Vec<Attribute>) -> Result<LitStr, Error>
         Some(str_name) => Ok(str_name),
     }
 }
-
-fn parse_field_attribute_data(field: &Field) -> Result<FieldAttributeData, Error> {
+fn parse_attribute_data(attrs: &[Attribute]) -> Result<FieldAttributeData, Error> {
     let mut name = None;
     let mut code = None;
     let mut required = false;
+    let mut amount = false;
     let mut is_inner_typ = false;
-    for attr in &field.attrs {
+    let mut seq = None;
+    let mut min = None;
+    let mut max = None;
+    let mut choice = false;
+    let mut max_len = None;
+    let mut pattern = None;
+    let mut range = None;
+    for attr in attrs {
         if attr.path().is_ident("ku") {
             attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("name") {
@@ -300,6 +912,20 @@ fn parse_field_attribute_data(field: &Field) -> Result<FieldAttributeData, Error
                     required = true;
                     return Ok(());
                 }
+                if meta.path.is_ident("amount") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let _: LitBool = content.parse()?;
+                    amount = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("seq") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitInt = content.parse()?;
+                    seq = Some(lit);
+                    return Ok(());
+                }
                 if meta.path.is_ident("inner_ty") {
                     let content;
                     parenthesized!(content in meta.input);
@@ -307,44 +933,401 @@ fn parse_field_attribute_data(field: &Field) -> Result<FieldAttributeData, Error
                     is_inner_typ = true;
                     return Ok(());
                 }
+                if meta.path.is_ident("min") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitInt = content.parse()?;
+                    min = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("max") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitInt = content.parse()?;
+                    max = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("max_len") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitInt = content.parse()?;
+                    max_len = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("pattern") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitStr = content.parse()?;
+                    pattern = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("range") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let min = parse_signed(&content)?;
+                    content.parse::<Token![,]>()?;
+                    let max = parse_signed(&content)?;
+                    if min > max {
+                        return Err(meta.error("the range is empty"));
+                    }
+                    range = Some((min, max));
+                    return Ok(());
+                }
+                if meta.path.is_ident("choice") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let _: LitBool = content.parse()?;
+                    choice = true;
+                    return Ok(());
+                }
                 Err(meta.error("unrecognized ku attributes"))
             })?;
         }
     }
 
-    let name =
-        name.ok_or_else(|| Error::new(field.span(), "must have a name ku(name(b\"...\"))"))?;
     Ok(FieldAttributeData {
         name,
         code,
         required,
+        amount,
         is_inner_type: is_inner_typ,
+        seq,
+        min,
+        max,
+        choice,
+        max_len,
+        pattern,
+        range,
     })
 }
 
+fn parse_signed(input: ParseStream) -> Result<i64, Error> {
+    let negative = input.parse::<Option<Token![-]>>()?.is_some();
+    let value: i64 = input.parse::<LitInt>()?.base10_parse()?;
+    Ok(if negative { -value } else { value })
+}
+
 struct FieldAttributeData {
-    name: LitByteStr,
+    name: Option<LitByteStr>,
     code: Option<LitStr>,
     required: bool,
+    /// An amount in kronor, rather than a count or an identifier
+    amount: bool,
     is_inner_type: bool,
+    seq: Option<LitInt>,
+    min: Option<LitInt>,
+    max: Option<LitInt>,
+    choice: bool,
+    max_len: Option<LitInt>,
+    pattern: Option<LitStr>,
+    range: Option<(i64, i64)>,
+}
+
+impl FieldAttributeData {
+    fn has_constraints(&self) -> bool {
+        self.max_len.is_some() || self.pattern.is_some() || self.range.is_some()
+    }
+}
+
+/// Checks of the constraints declared on a field, on a value `v` of it
+fn constraint_checks(attr: &FieldAttributeData, element: &str) -> TokenStream2 {
+    let faltkod = match &attr.code {
+        Some(code) => quote! { Some(#code) },
+        None => quote! { None },
+    };
+    let max_len = attr.max_len.as_ref().map(|max_len| {
+        quote! {
+            crate::validation::check_max_len(violations, #element, #faltkod, v, #max_len);
+        }
+    });
+    let pattern = attr.pattern.as_ref().map(|pattern| {
+        quote! {
+            crate::validation::check_pattern(violations, #element, #faltkod, v, {
+                static PATTERN: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+                    regex::Regex::new(concat!("^(?:", #pattern, ")$")).expect("invalid pattern in the ku attribute")
+                });
+                &PATTERN
+            }, #pattern);
+        }
+    });
+    let range = attr.range.map(|(min, max)| {
+        quote! {
+            crate::validation::check_range(violations, #element, #faltkod, v, #min, #max);
+        }
+    });
+    quote! {
+        #max_len
+        #pattern
+        #range
+    }
+}
+
+/// A field of a struct deriving `KontrolluppgiftRead` or `KontrolluppgiftWrite`
+struct FieldData {
+    attr: FieldAttributeData,
+    /// The xml element name, the field name for choices
+    name_str: String,
+    ident: Ident,
+    /// The variable holding the value while reading
+    temp: Ident,
+    ty: Type,
+    /// `Vec<T>` fields hold every occurrence of a repeated element
+    repeated: bool,
+}
+
+impl FieldData {
+    /// The type of a single element, `T` of `Vec<T>` or `Option<T>`
+    fn element_type(&self) -> &Type {
+        generic_argument(&self.ty, "Vec").unwrap_or_else(|| value_type(&self.ty))
+    }
+
+    fn min_occurs(&self) -> usize {
+        match &self.attr.min {
+            Some(min) => min.base10_parse().unwrap_or_default(),
+            None => self.attr.required as usize,
+        }
+    }
+}
+
+/// Parses the fields and sorts them into the order of the xsd sequence
+fn parse_fields(fields: FieldsNamed) -> Result<Vec<FieldData>, Error> {
+    fields
+        .named
+        .into_iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let attr = parse_attribute_data(&field.attrs)?;
+            let position = schema_position(&field, &attr)?;
+
+            let ident = field
+                .ident
+                .clone()
+                .ok_or_else(|| Error::new(field.span(), "Expected a named identifier"))?;
+            let repeated = generic_argument(&field.ty, "Vec").is_some();
+            if !repeated {
+                if let Some(lit) = attr.min.as_ref().or(attr.max.as_ref()) {
+                    return Err(Error::new(
+                        lit.span(),
+                        "min and max are only allowed on Vec fields",
+                    ));
+                }
+            }
+            let name_str = match (&attr.name, attr.choice) {
+                (Some(name), false) => String::from_utf8(name.value()).map_err(|_| {
+                    Error::new(name.span(), "the name is required to be valid utf-8")
+                })?,
+                (None, true) => ident.to_string(),
+                (Some(name), true) => {
+                    return Err(Error::new(
+                        name.span(),
+                        "choices are named by their variants, remove name",
+                    ))
+                }
+                (None, false) => {
+                    return Err(Error::new(
+                        field.span(),
+                        "must have a name ku(name(b\"...\"))",
+                    ))
+                }
+            };
+            if (attr.choice || attr.is_inner_type) && attr.has_constraints() {
+                return Err(Error::new(
+                    field.span(),
+                    "constraints are declared on the fields of the inner type",
+                ));
+            }
+            if attr.choice && (attr.code.is_some() || attr.is_inner_type) {
+                return Err(Error::new(
+                    field.span(),
+                    "choices can not have a faltkod or be an inner type",
+                ));
+            }
+            Ok((
+                position,
+                field.span(),
+                FieldData {
+                    attr,
+                    name_str,
+                    temp: Ident::new(&format!("g_field_{}", index), ident.span()),
+                    ident,
+                    ty: field.ty,
+                    repeated,
+                },
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()
+        .and_then(sort_by_schema)
+}
+
+/// The position of a field in the xsd sequence, from its `#[ku(seq(n))]`
+fn schema_position(field: &Field, data: &FieldAttributeData) -> Result<u32, Error> {
+    match &data.seq {
+        Some(seq) => seq.base10_parse(),
+        None => Err(Error::new(
+            field.span(),
+            "must have the position in the xsd sequence ku(seq(n))",
+        )),
+    }
+}
+
+/// Sorts the fields into the order of the xsd sequence
+fn sort_by_schema<T>(mut fields: Vec<(u32, Span, T)>) -> Result<Vec<T>, Error> {
+    fields.sort_by_key(|(position, _, _)| *position);
+    for pair in fields.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            return Err(Error::new(pair[1].1, "duplicate sequence position"));
+        }
+    }
+    Ok(fields.into_iter().map(|(_, _, field)| field).collect())
+}
+
+fn parse_variant_value(variant: &Variant) -> Result<String, Error> {
+    let mut value = None;
+    for attr in &variant.attrs {
+        if attr.path().is_ident("ku") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let lit: LitStr = content.parse()?;
+                    value = Some(lit.value());
+                    return Ok(());
+                }
+                Err(meta.error("Expected a name attribute on the variant #[ku(name(\"...\"))]"))
+            })?;
+        }
+    }
+    Ok(value.unwrap_or_else(|| variant.ident.to_string()))
+}
+
+/// A builder with a setter for every field, `build` fails when a required field is not set
+#[proc_macro_derive(KUBuilder, attributes(ku))]
+pub fn builder_macro(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let name = ast.ident;
+    let vis = ast.vis;
+    let builder = Ident::new(&format!("{}Builder", name), name.span());
+    let str_name = match parse_name_input(name.span(), ast.attrs) {
+        Ok(n) => n,
+        Err(e) => {
+            return e.into_compile_error().into();
+        }
+    };
+
+    let field = match parse_fields(get_fields(ast.data)) {
+        Ok(field) => field,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let builder_fields: Vec<_> = field
+        .iter()
+        .map(|f| {
+            let og = &f.ident;
+            let element_type = f.element_type();
+            if f.repeated {
+                quote! { #og: Vec<#element_type>, }
+            } else {
+                quote! { #og: Option<#element_type>, }
+            }
+        })
+        .collect();
+    let setters: Vec<_> = field
+        .iter()
+        .map(|f| {
+            let og = &f.ident;
+            let element_type = f.element_type();
+            if f.repeated {
+                quote! {
+                    pub fn #og(mut self, #og: impl IntoIterator<Item = #element_type>) -> Self {
+                        self.#og.extend(#og);
+                        self
+                    }
+                }
+            } else {
+                quote! {
+                    pub fn #og(mut self, #og: impl Into<#element_type>) -> Self {
+                        self.#og = Some(#og.into());
+                        self
+                    }
+                }
+            }
+        })
+        .collect();
+    let assignments: Vec<_> = field
+        .iter()
+        .map(|f| {
+            let og = &f.ident;
+            let str = &f.name_str;
+            if f.attr.required && !f.repeated {
+                quote! {
+                    #og: self.#og.ok_or_else(|| crate::Error::MissingElement { missing: #str.to_string(), reading: #str_name.to_string() })?,
+                }
+            } else {
+                quote! {
+                    #og: self.#og,
+                }
+            }
+        })
+        .collect();
+
+    let expanded = quote! {
+        #[doc = concat!("Builder for [", stringify!(#name), "], created by [", stringify!(#name), "::builder]")]
+        #[derive(Debug, Default)]
+        #vis struct #builder<'a> {
+            #(
+                #builder_fields
+            )*
+        }
+
+        impl<'a> #name<'a> {
+            pub fn builder() -> #builder<'a> {
+                Default::default()
+            }
+        }
+
+        impl<'a> #builder<'a> {
+            #(
+                #setters
+            )*
+
+            pub fn build(self) -> Result<#name<'a>, crate::error::Error> {
+                Ok(#name {
+                    #(
+                        #assignments
+                    )*
+                })
+            }
+        }
+    };
+    TokenStream::from(expanded)
 }
 
-#[proc_macro_derive(KUStringEnum)]
+#[proc_macro_derive(KUStringEnum, attributes(ku))]
 pub fn string_enum(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
 
     let variants = get_enum_fields(ast.data);
 
     let name = ast.ident;
-    let stuff: Vec<_> = variants
-        .into_iter()
-        .map(|e| (e.ident.clone(), e.ident.to_string()))
+    let stuff: Result<Vec<_>, Error> = variants
+        .iter()
+        .map(|e| Ok((e.ident.clone(), parse_variant_value(e)?)))
         .collect();
+    let stuff = match stuff {
+        Ok(stuff) => stuff,
+        Err(e) => return e.to_compile_error().into(),
+    };
     let strings: Vec<_> = stuff.iter().map(|e| &e.1).collect();
     let idents: Vec<_> = stuff.iter().map(|e| &e.0).collect();
 
     let expanded = quote! {
 
+        impl #name {
+            /// Every variant, in declaration order
+            pub const VARIANTS: &'static [#name] = &[#(#name::#idents,)*];
+        }
+
         impl<'a, 'b> crate::Readable<'a, 'b> for #name {
             fn get_str(data: Cow<str>) -> Result<Self, error::Error> {
                 match data.as_ref() {
@@ -354,6 +1337,16 @@ pub fn string_enum(input: TokenStream) -> TokenStream {
             }
         }
 
+        impl crate::value::FieldValue for #name {
+            fn to_value(&self) -> crate::value::Value {
+                crate::value::text_to_value(self)
+            }
+
+            fn from_value(value: crate::value::Value) -> Result<Self, crate::error::Error> {
+                crate::value::text_from_value(value)
+            }
+        }
+
         impl crate::Writable for #name {
             fn get_str(&self) -> Option<String> {
                 Some(match *self {
@@ -404,7 +1397,7 @@ pub fn variants_enum(input: TokenStream) -> TokenStream {
         .collect();
     let lits: Vec<_> = stuff
         .iter()
-        .map(|e| LitByteStr::new(&e.1.as_bytes(), Span::call_site()))
+        .map(|e| LitByteStr::new(e.1.as_bytes(), Span::call_site()))
         .collect();
     let idents: Vec<_> = stuff.iter().map(|e| &e.0).collect();
     let types: Vec<_> = stuff
@@ -428,16 +1421,52 @@ pub fn variants_enum(input: TokenStream) -> TokenStream {
             }
         }
 
+        #(
+            impl<'a> From<#types<'a>> for KontrolluppgiftType<'a> {
+                fn from(value: #types<'a>) -> Self {
+                    KontrolluppgiftType::#idents(value)
+                }
+            }
+        )*
+
+        impl<'a> crate::validation::Constraints for KontrolluppgiftType<'a> {
+            fn check_constraints(&self, violations: &mut Vec<crate::validation::Violation>) {
+                match self {
+                    #(#idents(v) => v.check_constraints(violations),)*
+                }
+            }
+        }
+
+        impl<'a> crate::value::FieldAccess for KontrolluppgiftType<'a> {
+            fn get_by_code(&self, faltkod: &str) -> Option<crate::value::Value> {
+                match self {
+                    #(#idents(v) => v.get_by_code(faltkod),)*
+                }
+            }
+
+            fn set_by_code(&mut self, faltkod: &str, value: crate::value::Value) -> Result<(), crate::error::Error> {
+                match self {
+                    #(#idents(v) => v.set_by_code(faltkod, value),)*
+                }
+            }
+
+            fn clear_by_code(&mut self, faltkod: &str) -> Result<(), crate::error::Error> {
+                match self {
+                    #(#idents(v) => v.clear_by_code(faltkod),)*
+                }
+            }
+        }
+
         impl<'a> KontrolluppgiftType<'a> {
 
-            fn read(reader: &mut NsReader<&'a [u8]>) -> Result<Option<Self>, Error> {
+            fn read(reader: &mut NsReader<&'a [u8]>, strict: bool) -> Result<Option<Self>, Error> {
                 let mut blankettinnehall = None;
 
                 loop {
                     match reader.read_event()? {
                         Event::Start(element) => match element.local_name().as_ref() {
                             #(#lits => {
-                                blankettinnehall = Some(crate::KontrolluppgiftType::#idents(#types::read(reader, &element)?));
+                                blankettinnehall = Some(crate::KontrolluppgiftType::#idents(#types::read(reader, &element, strict)?));
                                 break;
                             })*
                             &_ => unexpected_element(&element)?